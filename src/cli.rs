@@ -3,7 +3,7 @@ use std::process::exit;
 
 use clap::{Parser, Subcommand};
 
-use crate::{config::RawConfig, utils::log::LogManager};
+use crate::{config::RawConfig, dbus, utils::log::LogManager};
 
 #[derive(Parser)]
 #[command(version, about, long_about = None)]
@@ -11,12 +11,23 @@ use crate::{config::RawConfig, utils::log::LogManager};
 #[command(subcommand_value_name = "SUBCOMMAND")]
 #[command(subcommand_help_heading = "Subcommands")]
 pub struct Cli {
-    /// Path to config directory (default: `$XDG_CONFIG_HOME/frostbar/`)
+    /// Path to config directory (default: `$FROSTBAR_CONFIG_DIR`, or
+    /// `$XDG_CONFIG_HOME/frostbar/` if unset)
     ///
     /// Directory should contain a file named `config.kdl` and optionally a file named `colors.kdl`
     #[arg(short, long = "config", value_name = "DIRECTORY")]
     pub config_dir: Option<PathBuf>,
 
+    /// Print the config with defaults filled in and color variables
+    /// resolved, then exit
+    #[arg(long)]
+    pub print_config: bool,
+
+    /// Write the default config to the config directory, moving an existing
+    /// config to `config.kdl.bak`, then exit
+    #[arg(long)]
+    pub init_config: bool,
+
     #[command(subcommand)]
     pub subcommand: Option<SubCommand>,
 }
@@ -33,6 +44,17 @@ pub enum SubCommand {
         #[arg(short, long)]
         pid: Option<u32>,
     },
+    /// Query the state of a running bar
+    Query {
+        #[command(subcommand)]
+        target: QueryTarget,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum QueryTarget {
+    /// Print the index and name of the focused niri workspace
+    Workspace,
 }
 
 #[derive(Subcommand, Default)]
@@ -64,6 +86,40 @@ pub fn handle_subcommand(sub: SubCommand, log_manager: &LogManager) {
                 println!("no log files found");
             }
         }
+        SubCommand::Query { target } => match target {
+            QueryTarget::Workspace => match dbus::query_workspace() {
+                Ok((idx, name)) if name.is_empty() => println!("{idx}"),
+                Ok((idx, name)) => println!("{idx} {name}"),
+                Err(e) => {
+                    eprintln!("{e}");
+                    exit(1);
+                }
+            },
+        },
     }
     exit(0);
 }
+
+pub fn print_config(config_dir: Option<PathBuf>) -> ! {
+    if let Err(e) = RawConfig::print_resolved(config_dir) {
+        eprintln!("{e:?}");
+        exit(1);
+    }
+    exit(0);
+}
+
+pub fn init_config(config_dir: Option<PathBuf>) -> ! {
+    match RawConfig::reset(config_dir) {
+        Ok((config_path, backup)) => {
+            if let Some(backup) = backup {
+                println!("moved old config to {}", backup.display());
+            }
+            println!("wrote default config to {}", config_path.display());
+            exit(0);
+        }
+        Err(e) => {
+            eprintln!("{e:?}");
+            exit(1);
+        }
+    }
+}
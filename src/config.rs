@@ -5,24 +5,33 @@ use std::{
     ops::{Deref, DerefMut},
     path::{Path, PathBuf},
     str::FromStr,
+    time::Duration,
 };
 
-use iced::{Background, Color, border, color, widget::container};
+use iced::{
+    Background, Color, Font, alignment, border, color, font, mouse,
+    widget::{container, text::Shaping},
+};
 use knus::{
     Decode, DecodeScalar, ast::Literal, decode::Kind, errors::DecodeError,
 };
 use miette::{Context, IntoDiagnostic};
 use owo_colors::OwoColorize;
+use parking_lot::Mutex;
 use rustc_hash::FxHashMap;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 use crate::{
-    CommandSpec, Message,
+    CommandSpec, FIRA_CODE, Message,
     file_watcher::ConfigPath,
     modules::{BarAlignment, BarPosition},
     utils::log::notification,
 };
 
+mod print;
+
+const CONFIG_DIR_ENV: &str = "FROSTBAR_CONFIG_DIR";
+
 const DEFAULT_CONFIG: &[u8] = include_bytes!("../assets/default-config.kdl");
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -117,6 +126,46 @@ impl<S: knus::traits::ErrorSpan> knus::DecodeScalar<S> for FloatOrPercent {
     }
 }
 
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpacerSize {
+    Fixed(f32),
+    Fill,
+}
+
+impl<S: knus::traits::ErrorSpan> knus::DecodeScalar<S> for SpacerSize {
+    fn type_check(
+        type_name: &Option<knus::span::Spanned<knus::ast::TypeName, S>>,
+        ctx: &mut knus::decode::Context<S>,
+    ) {
+        if let Some(type_name) = &type_name {
+            ctx.emit_error(DecodeError::unexpected(
+                type_name,
+                "type name",
+                "no type name expected for this node",
+            ));
+        }
+    }
+
+    fn raw_decode(
+        val: &knus::span::Spanned<knus::ast::Literal, S>,
+        ctx: &mut knus::decode::Context<S>,
+    ) -> Result<Self, DecodeError<S>> {
+        match &**val {
+            knus::ast::Literal::String(value) if &**value == "fill" => {
+                Ok(SpacerSize::Fill)
+            }
+            knus::ast::Literal::String(_) => Err(DecodeError::unsupported(
+                val,
+                "expected a number or `fill`",
+            )),
+            _ => {
+                let size = FloatOrInt::<0, { i32::MAX }>::raw_decode(val, ctx)?;
+                Ok(SpacerSize::Fixed(size.into()))
+            }
+        }
+    }
+}
+
 #[derive(Debug, Default, Clone, Copy, PartialEq)]
 pub struct FloatOrInt<const MIN: i32, const MAX: i32>(pub f32);
 
@@ -218,11 +267,33 @@ pub struct RawConfig {
     #[knus(child, default)]
     pub style: RawTopLevelStyle,
     #[knus(child, default)]
+    gestures: RawGestures,
+    #[knus(child, default)]
+    icons: Icons,
+    #[knus(child, default)]
+    notifications: Notifications,
+    #[knus(child, default)]
+    animations: RawAnimations,
+    #[knus(child, default)]
+    tooltips: RawTooltips,
+    #[knus(child, default)]
     pub start: Start,
     #[knus(child, default)]
     pub middle: Middle,
     #[knus(child, default)]
     pub end: End,
+
+    // aliases for start/middle/end that read naturally for one orientation
+    #[knus(child, default)]
+    top: Start,
+    #[knus(child, default)]
+    left: Start,
+    #[knus(child, default)]
+    center: Middle,
+    #[knus(child, default)]
+    bottom: End,
+    #[knus(child, default)]
+    right: End,
 }
 
 impl Default for RawConfig {
@@ -234,20 +305,69 @@ impl Default for RawConfig {
 pub struct Config {
     pub layout: Layout,
     pub style: TopLevelStyle,
+    pub gestures: Gestures,
+    pub icons: Icons,
+    pub notifications: Notifications,
+    pub animations: Animations,
+    pub tooltips: Tooltips,
+    /// Binds for the parts of the bar that aren't covered by a module bind
+    pub bar_binds: MouseBinds,
     pub modules: ConfigModules,
 }
 
 impl RawConfig {
-    pub fn hydrate(self, colors: &ColorVars) -> Config {
+    pub fn hydrate(mut self, colors: &ColorVars) -> Config {
+        let sections = self.sections();
+        let bar_binds = std::mem::take(&mut self.layout.binds).hydrate();
         Config {
             layout: self.layout,
             style: self.style.hydrate(colors),
-            modules: hydrate_modules(
-                (self.start, self.middle, self.end),
-                colors,
-            ),
+            gestures: self.gestures.hydrate(),
+            icons: std::mem::take(&mut self.icons),
+            notifications: self.notifications,
+            animations: self.animations.hydrate(),
+            tooltips: self.tooltips.hydrate(),
+            bar_binds,
+            modules: hydrate_modules(sections, colors),
         }
     }
+
+    /// Merge the section aliases into start, middle and end. `top`/`bottom`
+    /// only make sense for vertical bars and `left`/`right` for horizontal
+    /// ones, so using the other pair is allowed but warned about.
+    fn sections(&mut self) -> (Start, Middle, End) {
+        let vertical = self.layout.anchor.vertical();
+        for (name, modules, fits) in [
+            ("top", &self.top.modules, vertical),
+            ("bottom", &self.bottom.modules, vertical),
+            ("left", &self.left.modules, !vertical),
+            ("right", &self.right.modules, !vertical),
+        ] {
+            if !fits && !modules.is_empty() {
+                warn!(
+                    "section `{name}` does not match the bar's orientation, treating it as {}",
+                    if matches!(name, "top" | "left") {
+                        "`start`"
+                    } else {
+                        "`end`"
+                    }
+                );
+            }
+        }
+
+        let mut start = std::mem::take(&mut self.start);
+        start.modules.append(&mut self.top.modules);
+        start.modules.append(&mut self.left.modules);
+
+        let mut middle = std::mem::take(&mut self.middle);
+        middle.modules.append(&mut self.center.modules);
+
+        let mut end = std::mem::take(&mut self.end);
+        end.modules.append(&mut self.bottom.modules);
+        end.modules.append(&mut self.right.modules);
+
+        (start, middle, end)
+    }
 }
 
 fn hydrate_modules(
@@ -260,29 +380,7 @@ fn hydrate_modules(
         |mut module_configs: Vec<RawConfigModule>, align: BarAlignment| {
             for (idx, module_config) in module_configs.drain(..).enumerate() {
                 let position = BarPosition { idx, align };
-                match module_config {
-                    RawConfigModule::Battery(c) => {
-                        modules.push((c.hydrate(colors), position));
-                    }
-                    RawConfigModule::AudioVisualizer(c) => {
-                        modules.push((c.hydrate(colors), position));
-                    }
-                    RawConfigModule::Mpris(c) => {
-                        modules.push((c.hydrate(colors), position));
-                    }
-                    RawConfigModule::Time(c) => {
-                        modules.push((c.hydrate(colors), position));
-                    }
-                    RawConfigModule::Label(c) => {
-                        modules.push((c.hydrate(colors), position));
-                    }
-                    RawConfigModule::Niri(c) => {
-                        modules.push((c.hydrate(colors), position));
-                    }
-                    RawConfigModule::SystemTray(c) => {
-                        modules.push((c.hydrate(colors), position));
-                    }
-                }
+                modules.push((module_config.hydrate(colors), position));
             }
         };
 
@@ -315,16 +413,197 @@ pub struct End {
 pub struct Layout {
     #[knus(child, unwrap(argument), default = 42)]
     pub width: u32,
-    #[knus(child, unwrap(argument), default = 0)]
-    pub gaps: i32,
+    #[knus(child, default = Gaps::splat(0))]
+    pub gaps: Gaps,
+    /// Space taken up by other bars along the screen edges, gaps included
+    #[knus(child, default)]
+    pub other_bars: Gaps,
+    #[knus(child, unwrap(argument), default)]
+    pub corners: Corners,
     #[knus(child, unwrap(argument), default = Self::default().anchor)]
     pub anchor: Anchor,
     #[knus(child, unwrap(argument), default = Self::default().layer)]
     pub layer: Layer,
+    #[knus(child, unwrap(argument))]
+    pub section_spacing: Option<FloatOrInt<0, { i32::MAX }>>,
+    #[knus(child)]
+    pub avoid_region: Option<AvoidRegion>,
+    #[knus(child, unwrap(argument), default = Self::default().screen_corner_radius)]
+    pub screen_corner_radius: FloatOrInt<0, { i32::MAX }>,
+    #[knus(child, unwrap(argument), default = Self::default().keyboard_interactivity)]
+    pub keyboard_interactivity: KeyboardInteractivity,
+    #[knus(child, unwrap(argument), default)]
+    pub middle_mode: MiddleMode,
+    #[knus(child, unwrap(argument), default)]
+    pub scale: Scale,
+    #[knus(child, unwrap(argument), default)]
+    pub scale_mode: ScaleMode,
+    #[knus(child, unwrap(argument), default)]
+    pub when_empty: WhenEmpty,
+    #[knus(child, unwrap(argument))]
+    pub compact_below: Option<u32>,
+    /// Moved to [`Config::bar_binds`] when hydrating
+    #[knus(flatten(child), default)]
+    binds: RawMouseBinds,
 }
 
-pub const fn splat_gaps(gaps: i32) -> (i32, i32, i32, i32) {
-    (gaps, gaps, gaps, gaps)
+/// What to do with the bar when no modules are configured
+#[derive(knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Default)]
+pub enum WhenEmpty {
+    /// Show the empty bar, without reserving space for it
+    #[default]
+    Show,
+    /// Don't open the bar at all
+    Hide,
+}
+
+/// Factor that everything on the bar is scaled by
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Scale {
+    /// As configured
+    pub factor: f32,
+    /// Scale of the output the bar is on, as reported by the compositor.
+    /// Not part of the config, see [`Layout::factor`].
+    pub output: f32,
+}
+
+impl Default for Scale {
+    fn default() -> Self {
+        Self {
+            factor: 1.0,
+            output: 1.0,
+        }
+    }
+}
+
+/// Which pixels the sizes in the config are in
+#[derive(knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ScaleMode {
+    /// Logical pixels, which the compositor scales with the output
+    #[default]
+    Logical,
+    /// Physical pixels of the output, so the bar keeps its size in pixels
+    /// whatever the output's scale
+    Physical,
+}
+
+impl<S: knus::traits::ErrorSpan> knus::DecodeScalar<S> for Scale {
+    fn type_check(
+        type_name: &Option<knus::span::Spanned<knus::ast::TypeName, S>>,
+        ctx: &mut knus::decode::Context<S>,
+    ) {
+        FloatOrInt::<0, 16>::type_check(type_name, ctx);
+    }
+
+    fn raw_decode(
+        val: &knus::span::Spanned<knus::ast::Literal, S>,
+        ctx: &mut knus::decode::Context<S>,
+    ) -> Result<Self, DecodeError<S>> {
+        let scale = FloatOrInt::<0, 16>::raw_decode(val, ctx)?.into_f32();
+        if scale > 0.0 {
+            Ok(Scale {
+                factor: scale,
+                ..Scale::default()
+            })
+        } else {
+            ctx.emit_error(DecodeError::unsupported(
+                val,
+                "scale must be greater than 0",
+            ));
+            Ok(Scale::default())
+        }
+    }
+}
+
+/// How the modules of the middle section are centered
+#[derive(knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Default)]
+pub enum MiddleMode {
+    /// Center the modules together, as one group
+    #[default]
+    Group,
+    /// Give each module an equal share of the bar and center it there
+    Distribute,
+}
+
+/// Horizontal span of the output, in logical pixels from its left edge, that
+/// the middle section of a horizontal bar keeps clear, e.g. a display notch
+#[derive(knus::Decode, Debug, Clone, Copy, PartialEq)]
+pub struct AvoidRegion {
+    #[knus(child, unwrap(argument))]
+    pub x: FloatOrInt<0, { i32::MAX }>,
+    #[knus(child, unwrap(argument))]
+    pub width: FloatOrInt<0, { i32::MAX }>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct Gaps {
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+    pub left: i32,
+}
+
+#[derive(knus::Decode, Debug, Clone, Default)]
+struct PerSide {
+    #[knus(child, unwrap(argument), default)]
+    top: i32,
+    #[knus(child, unwrap(argument), default)]
+    right: i32,
+    #[knus(child, unwrap(argument), default)]
+    bottom: i32,
+    #[knus(child, unwrap(argument), default)]
+    left: i32,
+}
+
+impl From<PerSide> for Gaps {
+    fn from(sides: PerSide) -> Self {
+        Self {
+            top: sides.top,
+            right: sides.right,
+            bottom: sides.bottom,
+            left: sides.left,
+        }
+    }
+}
+
+impl Gaps {
+    pub const fn splat(gaps: i32) -> Self {
+        Self {
+            top: gaps,
+            right: gaps,
+            bottom: gaps,
+            left: gaps,
+        }
+    }
+
+    /// top, right, bottom, left
+    pub const fn margin(self) -> (i32, i32, i32, i32) {
+        (self.top, self.right, self.bottom, self.left)
+    }
+
+    /// The gap between the bar and the screen edge it is anchored to
+    pub const fn edge(self, anchor: Anchor) -> i32 {
+        match anchor {
+            Anchor::Left => self.left,
+            Anchor::Right => self.right,
+            Anchor::Top => self.top,
+            Anchor::Bottom => self.bottom,
+        }
+    }
+}
+
+/// Which bar gets a screen corner that is shared with one of
+/// [`Layout::other_bars`]
+#[derive(knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Default)]
+pub enum Corners {
+    /// Bars along the top and bottom own their corners, bars along the left
+    /// and right yield them
+    #[default]
+    Auto,
+    /// Span the whole edge, the other bar has to make room
+    Own,
+    /// Start after the other bar
+    Yield,
 }
 
 #[derive(knus::DecodeScalar, Debug, Clone, Copy, PartialEq)]
@@ -384,23 +663,314 @@ impl From<Layer> for iced_layershell::reexport::Layer {
     }
 }
 
+/// Whether the bar can take keyboard focus
+#[derive(knus::DecodeScalar, Debug, Clone, Copy, PartialEq)]
+pub enum KeyboardInteractivity {
+    None,
+    /// Focus is given to the bar when clicked, like a normal window
+    OnDemand,
+    /// The bar grabs the keyboard while it is on the top or overlay layer
+    Exclusive,
+}
+
+impl From<KeyboardInteractivity>
+    for iced_layershell::reexport::KeyboardInteractivity
+{
+    fn from(value: KeyboardInteractivity) -> Self {
+        use iced_layershell::reexport::KeyboardInteractivity as wKeyboard;
+        match value {
+            KeyboardInteractivity::None => wKeyboard::None,
+            KeyboardInteractivity::OnDemand => wKeyboard::OnDemand,
+            KeyboardInteractivity::Exclusive => wKeyboard::Exclusive,
+        }
+    }
+}
+
+impl Layout {
+    /// Distance of the bar's surface from the screen edges, as top, right,
+    /// bottom, left. Where the bar yields a corner to one of `other_bars`,
+    /// the space of that bar is added to the gap.
+    pub fn margin(&self) -> (i32, i32, i32, i32) {
+        let yields = match self.corners {
+            Corners::Auto => self.anchor.vertical(),
+            Corners::Own => false,
+            Corners::Yield => true,
+        };
+
+        let mut gaps = self.gaps;
+        // only bars along the edges next to this one share a corner with it
+        if yields && self.anchor.vertical() {
+            gaps.top += self.other_bars.top;
+            gaps.bottom += self.other_bars.bottom;
+        } else if yields {
+            gaps.left += self.other_bars.left;
+            gaps.right += self.other_bars.right;
+        }
+        gaps.margin()
+    }
+
+    pub fn exclusive_zone(&self) -> i32 {
+        self.scaled_width() as i32 + self.gaps.edge(self.anchor)
+    }
+
+    /// Width of the bar on screen, in logical pixels of the output. Inside
+    /// the bar iced applies the scale, so views keep using `width`.
+    pub fn scaled_width(&self) -> u32 {
+        (self.width as f32 * self.factor()).round() as u32
+    }
+
+    /// Factor that the bar is scaled by, from the pixels of the config to
+    /// logical pixels of the output
+    pub fn factor(&self) -> f32 {
+        match self.scale_mode {
+            ScaleMode::Logical => self.scale.factor,
+            ScaleMode::Physical => self.scale.factor / self.scale.output,
+        }
+    }
+
+    /// Size of the bar's layer surface
+    pub fn surface_size(&self) -> (u32, u32) {
+        self.anchor.calc_size(self.scaled_width())
+    }
+
+    /// Whether the bar is narrower than `compact_below`, in which case
+    /// modules leave out the parts that don't fit well on a narrow bar
+    pub fn compact(&self) -> bool {
+        self.compact_below
+            .is_some_and(|threshold| self.scaled_width() < threshold)
+    }
+
+    /// Minimum gap between modules in a section. Defaults to 5 for horizontal
+    /// bars and nothing for vertical ones.
+    pub fn section_spacing(&self) -> f32 {
+        self.section_spacing.map_or_else(
+            || if self.anchor.vertical() { 0.0 } else { 5.0 },
+            Into::into,
+        )
+    }
+}
+
 impl Default for Layout {
     fn default() -> Self {
         Self {
             width: 42,
-            gaps: 3,
+            gaps: Gaps::splat(3),
+            other_bars: Gaps::default(),
+            corners: Corners::Auto,
             anchor: Anchor::Left,
             layer: Layer::Top,
+            section_spacing: None,
+            avoid_region: None,
+            screen_corner_radius: 0.0.into(),
+            keyboard_interactivity: KeyboardInteractivity::None,
+            middle_mode: MiddleMode::default(),
+            scale: Scale::default(),
+            scale_mode: ScaleMode::default(),
+            when_empty: WhenEmpty::default(),
+            compact_below: None,
+            binds: RawMouseBinds::default(),
+        }
+    }
+}
+
+#[derive(knus::Decode, Debug, Clone)]
+pub struct RawGestures {
+    #[knus(child, unwrap(argument), default = Self::default().scroll_threshold)]
+    pub scroll_threshold: FloatOrInt<0, { i32::MAX }>,
+    #[knus(child, unwrap(argument), default = Self::default().scroll_timeout)]
+    pub scroll_timeout: u64,
+    #[knus(child, unwrap(argument), default = Self::default().click_suppression)]
+    pub click_suppression: u64,
+}
+
+impl Default for RawGestures {
+    fn default() -> Self {
+        Self {
+            scroll_threshold: 0.0.into(),
+            scroll_timeout: 300,
+            click_suppression: 0,
+        }
+    }
+}
+
+impl RawGestures {
+    fn hydrate(self) -> Gestures {
+        Gestures {
+            scroll_threshold: self.scroll_threshold.into(),
+            scroll_timeout: Duration::from_millis(self.scroll_timeout),
+            click_suppression: Duration::from_millis(self.click_suppression),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Gestures {
+    /// Scroll distance that has to accumulate before a scroll bind fires
+    pub scroll_threshold: f32,
+    /// Time without scroll events after which a scroll gesture ends
+    pub scroll_timeout: Duration,
+    /// Time after the last scroll event during which clicks are ignored
+    pub click_suppression: Duration,
+}
+
+#[derive(knus::Decode, Debug, Clone, Copy)]
+pub struct RawAnimations {
+    #[knus(child, unwrap(argument), default = Self::default().duration)]
+    pub duration: u64,
+}
+
+impl Default for RawAnimations {
+    fn default() -> Self {
+        Self { duration: 150 }
+    }
+}
+
+impl RawAnimations {
+    fn hydrate(self) -> Animations {
+        Animations {
+            duration: Duration::from_millis(self.duration),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Animations {
+    /// Length of style transitions, zero to switch instantly
+    pub duration: Duration,
+}
+
+#[derive(knus::Decode, Debug, Clone, Copy)]
+pub struct RawTooltips {
+    #[knus(child, unwrap(argument), default = Self::default().timeout)]
+    pub timeout: u64,
+    #[knus(child, unwrap(argument), default)]
+    pub text_align: TextAlign,
+    #[knus(child, unwrap(argument), default)]
+    pub pin_on: PinOn,
+}
+
+impl Default for RawTooltips {
+    fn default() -> Self {
+        Self {
+            timeout: 5,
+            text_align: TextAlign::default(),
+            pin_on: PinOn::default(),
         }
     }
 }
 
+impl RawTooltips {
+    fn hydrate(self) -> Tooltips {
+        Tooltips {
+            timeout: Duration::from_secs(self.timeout),
+            text_align: self.text_align,
+            pin_on: self.pin_on,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Tooltips {
+    /// Time after which a tooltip is closed even if the pointer never left,
+    /// zero to keep tooltips open
+    pub timeout: Duration,
+    /// How the lines of multi-line tooltip text are aligned to each other
+    pub text_align: TextAlign,
+    /// The button that pins an open tooltip, if any
+    pub pin_on: PinOn,
+}
+
+/// Which click on a module keeps its tooltip open until clicked away
+#[derive(knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Default)]
+pub enum PinOn {
+    /// Tooltips can't be pinned
+    #[default]
+    None,
+    MouseRight,
+    MouseMiddle,
+}
+
+impl PinOn {
+    pub fn button(self) -> Option<mouse::Button> {
+        match self {
+            PinOn::None => None,
+            PinOn::MouseRight => Some(mouse::Button::Right),
+            PinOn::MouseMiddle => Some(mouse::Button::Middle),
+        }
+    }
+}
+
+#[derive(knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Default)]
+pub enum TextAlign {
+    #[default]
+    Left,
+    Center,
+    Right,
+}
+
+impl From<TextAlign> for alignment::Horizontal {
+    fn from(value: TextAlign) -> Self {
+        match value {
+            TextAlign::Left => alignment::Horizontal::Left,
+            TextAlign::Center => alignment::Horizontal::Center,
+            TextAlign::Right => alignment::Horizontal::Right,
+        }
+    }
+}
+
+#[derive(knus::Decode, Debug, Clone, Copy)]
+pub struct Notifications {
+    /// Whether to send any desktop notifications at all
+    #[knus(child, unwrap(argument), default = true)]
+    pub enabled: bool,
+    /// Notify when a bind's command can't be spawned
+    #[knus(child, unwrap(argument), default = false)]
+    pub command_failures: bool,
+}
+
+impl Default for Notifications {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            command_failures: false,
+        }
+    }
+}
+
+#[derive(knus::Decode, Debug, Clone, Default, PartialEq)]
+pub struct Icons {
+    #[knus(children(name = "override"))]
+    pub overrides: Vec<IconOverride>,
+    #[knus(child, unwrap(argument))]
+    pub fallback: Option<String>,
+}
+
+/// Use `icon` for windows with this `app_id`. The icon may be an icon name
+/// or an absolute path to an image.
+#[derive(knus::Decode, Debug, Clone, PartialEq)]
+pub struct IconOverride {
+    #[knus(argument)]
+    pub app_id: String,
+    #[knus(argument)]
+    pub icon: String,
+}
+
 #[derive(knus::Decode, Debug, Clone)]
 pub struct RawTopLevelStyle {
     #[knus(child, default = Self::default().border_radius)]
     pub border_radius: RawConfigRadius,
     #[knus(child, unwrap(argument), default = Self::default().background)]
     pub background: ConfigColor,
+    #[knus(child, unwrap(argument))]
+    pub icon_font: Option<String>,
+    #[knus(children(name = "font-file"))]
+    pub font_files: Vec<RawFontFile>,
+}
+
+#[derive(knus::Decode, Debug)]
+pub struct RawFontFile {
+    #[knus(argument)]
+    pub path: String,
 }
 
 impl Default for RawTopLevelStyle {
@@ -408,6 +978,8 @@ impl Default for RawTopLevelStyle {
         Self {
             border_radius: RawConfigRadius::All(0.0.into()),
             background: Color::from_rgb(0.0, 0.0, 0.0).into(),
+            icon_font: None,
+            font_files: Vec::new(),
         }
     }
 }
@@ -415,6 +987,10 @@ impl Default for RawTopLevelStyle {
 pub struct TopLevelStyle {
     pub border_radius: border::Radius,
     pub background: Color,
+    /// Font for icon glyphs, e.g. a nerd font
+    pub icon_font: Font,
+    /// Font files to load on top of the installed fonts
+    pub font_files: Vec<PathBuf>,
 }
 
 impl RawTopLevelStyle {
@@ -422,10 +998,35 @@ impl RawTopLevelStyle {
         TopLevelStyle {
             border_radius: self.border_radius.into(),
             background: self.background.resolve(colors),
+            icon_font: self.icon_font.map_or(FIRA_CODE, |family| Font {
+                family: font_family(family),
+                ..Font::DEFAULT
+            }),
+            font_files: self
+                .font_files
+                .into_iter()
+                .map(|file| PathBuf::from(file.path))
+                .collect(),
         }
     }
 }
 
+/// `Font` only takes static family names, so each name is leaked once and
+/// reused across reloads
+fn font_family(name: String) -> font::Family {
+    static FAMILIES: Mutex<Vec<&'static str>> = Mutex::new(Vec::new());
+
+    let mut families = FAMILIES.lock();
+    let name = if let Some(family) = families.iter().find(|f| **f == name) {
+        family
+    } else {
+        let family: &'static str = name.leak();
+        families.push(family);
+        family
+    };
+    font::Family::Name(name)
+}
+
 #[derive(knus::Decode, Debug)]
 struct ColorVariable {
     #[knus(node_name)]
@@ -434,7 +1035,7 @@ struct ColorVariable {
     pub color: ConfigColor,
 }
 
-#[derive(Debug, Default)]
+#[derive(Debug, Clone, Default)]
 pub struct ColorVars {
     vars: FxHashMap<String, Color>,
 }
@@ -463,9 +1064,31 @@ pub enum RawConfigModule {
     Battery(RawBattery),
     Time(RawTime),
     Mpris(RawMpris),
+    NowPlaying(RawNowPlaying),
     Niri(Box<RawNiri>),
+    ColumnIndicator(RawColumnIndicator),
     Label(RawLabel),
+    Spacer(RawSpacer),
     SystemTray(RawSystemTray),
+    Group(RawGroup),
+}
+
+impl RawConfigModule {
+    fn hydrate(self, colors: &ColorVars) -> ConfigModule {
+        match self {
+            RawConfigModule::Battery(c) => c.hydrate(colors),
+            RawConfigModule::AudioVisualizer(c) => c.hydrate(colors),
+            RawConfigModule::Mpris(c) => c.hydrate(colors),
+            RawConfigModule::NowPlaying(c) => c.hydrate(colors),
+            RawConfigModule::Time(c) => c.hydrate(colors),
+            RawConfigModule::Label(c) => c.hydrate(colors),
+            RawConfigModule::Spacer(c) => c.hydrate(colors),
+            RawConfigModule::Niri(c) => c.hydrate(colors),
+            RawConfigModule::ColumnIndicator(c) => c.hydrate(colors),
+            RawConfigModule::SystemTray(c) => c.hydrate(colors),
+            RawConfigModule::Group(c) => c.hydrate(colors),
+        }
+    }
 }
 
 pub enum ConfigModule {
@@ -473,9 +1096,13 @@ pub enum ConfigModule {
     Battery(Battery),
     Time(Time),
     Mpris(Mpris),
+    NowPlaying(NowPlaying),
     Niri(Box<Niri>),
+    ColumnIndicator(ColumnIndicator),
     Label(Label),
+    Spacer(Spacer),
     SystemTray(SystemTray),
+    Group(Group),
 }
 
 pub struct ConfigModules {
@@ -510,6 +1137,12 @@ pub struct RawAudioVisualizer {
     #[knus(child, unwrap(argument), default = Self::default().dynamic_color)]
     pub dynamic_color: bool,
 
+    #[knus(child, unwrap(argument), default = Self::default().grow)]
+    pub grow: Grow,
+
+    #[knus(child, unwrap(argument), default = Self::default().follow_player)]
+    pub follow_player: String,
+
     #[knus(flatten(child), default)]
     pub binds: RawMouseBinds,
 
@@ -523,6 +1156,8 @@ impl Default for RawAudioVisualizer {
             length: 130,
             spacing: FloatOrPercent::Percent(0.1),
             dynamic_color: true,
+            grow: Grow::Bottom,
+            follow_player: "active".to_string(),
             color: Color::WHITE.into(),
             binds: RawMouseBinds::default(),
             style: RawContainerStyle::default(),
@@ -537,6 +1172,8 @@ impl RawAudioVisualizer {
             spacing: self.spacing,
             color: self.color.resolve(colors),
             dynamic_color: self.dynamic_color,
+            grow: self.grow,
+            follow_player: self.follow_player.into(),
             binds: self.binds.hydrate(),
             style: self.style.hydrate(colors),
         };
@@ -550,10 +1187,44 @@ pub struct AudioVisualizer {
     pub spacing: FloatOrPercent,
     pub color: Color,
     pub dynamic_color: bool,
+    pub grow: Grow,
+    pub follow_player: FollowPlayer,
     pub binds: MouseBinds,
     pub style: ContainerStyle,
 }
 
+/// Which mpris player the audio visualizer takes its colors from
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum FollowPlayer {
+    /// The first playing player
+    #[default]
+    Active,
+    /// A player by name, e.g. `spotify` for `org.mpris.MediaPlayer2.spotify`
+    Named(String),
+}
+
+impl From<String> for FollowPlayer {
+    fn from(value: String) -> Self {
+        if value == "active" {
+            FollowPlayer::Active
+        } else {
+            FollowPlayer::Named(value)
+        }
+    }
+}
+
+/// Where audio visualizer bars grow from on the bar's cross axis. On
+/// vertical bars, top is the left edge and bottom the right edge.
+#[derive(knus::DecodeScalar, Debug, Clone, Copy, PartialEq)]
+pub enum Grow {
+    /// Both channels grow from the bottom (or right) edge
+    Bottom,
+    /// Both channels grow from the top (or left) edge
+    Top,
+    /// Left channel grows towards the top, right channel towards the bottom
+    Center,
+}
+
 #[derive(knus::Decode, Debug)]
 pub struct RawBattery {
     #[knus(child, unwrap(argument), default = Self::default().icon_size)]
@@ -562,9 +1233,42 @@ pub struct RawBattery {
     #[knus(child, unwrap(argument), default = Self::default().charging_color)]
     pub charging_color: ConfigColor,
 
+    #[knus(child, unwrap(argument), default = Self::default().interval)]
+    pub interval: u64,
+
+    #[knus(child, unwrap(argument), default = Self::default().show_percentage)]
+    pub show_percentage: bool,
+
+    #[knus(child, unwrap(argument), default = Self::default().text_size)]
+    pub text_size: u32,
+
+    #[knus(child, unwrap(argument), default)]
+    pub icon_position: IconPosition,
+
+    #[knus(child, unwrap(argument), default = Self::default().precision)]
+    pub precision: u8,
+
+    #[knus(child, unwrap(argument))]
+    pub round_to: Option<u8>,
+
+    #[knus(child, unwrap(argument), default = Self::default().smoothing)]
+    pub smoothing: u8,
+
+    #[knus(child, unwrap(argument), default = Self::default().show_tooltip)]
+    pub show_tooltip: bool,
+
+    #[knus(child, unwrap(argument), default = Self::default().tooltip_format)]
+    pub tooltip_format: String,
+
     #[knus(child, default)]
     pub style: RawContainerStyle,
 
+    #[knus(child, default)]
+    pub icon_style: RawContainerStyle,
+
+    #[knus(child, default)]
+    pub text_style: RawContainerStyle,
+
     #[knus(flatten(child), default)]
     pub binds: RawMouseBinds,
 }
@@ -574,7 +1278,19 @@ impl Default for RawBattery {
         Self {
             icon_size: 22,
             charging_color: color!(0x73F5AB).into(),
+            interval: 30,
+            show_percentage: false,
+            text_size: 14,
+            icon_position: IconPosition::default(),
+            precision: 0,
+            round_to: None,
+            smoothing: 1,
+            show_tooltip: true,
+            tooltip_format: "Battery {index}: {percentage}% ({state})"
+                .to_string(),
             style: RawContainerStyle::default(),
+            icon_style: RawContainerStyle::default(),
+            text_style: RawContainerStyle::default(),
             binds: RawMouseBinds::default(),
         }
     }
@@ -585,7 +1301,18 @@ impl RawBattery {
         let battery = Battery {
             icon_size: self.icon_size,
             charging_color: self.charging_color.resolve(colors),
+            interval: Duration::from_secs(self.interval.max(1)),
+            show_percentage: self.show_percentage,
+            text_size: self.text_size,
+            icon_position: self.icon_position,
+            precision: self.precision,
+            round_to: self.round_to.filter(|step| *step > 1),
+            smoothing: self.smoothing.max(1),
+            show_tooltip: self.show_tooltip,
+            tooltip_format: self.tooltip_format,
             style: self.style.hydrate(colors),
+            icon_style: self.icon_style.hydrate(colors),
+            text_style: self.text_style.hydrate(colors),
             binds: self.binds.hydrate(),
         };
 
@@ -596,7 +1323,22 @@ impl RawBattery {
 pub struct Battery {
     pub icon_size: u32,
     pub charging_color: Color,
+    pub interval: Duration,
+    pub show_percentage: bool,
+    pub text_size: u32,
+    pub icon_position: IconPosition,
+    /// Decimal places of the percentage
+    pub precision: u8,
+    /// Step to round the percentage to, e.g. 5
+    pub round_to: Option<u8>,
+    /// Number of readings to average
+    pub smoothing: u8,
+    pub show_tooltip: bool,
+    /// Format of each battery's part of the tooltip
+    pub tooltip_format: String,
     pub style: ContainerStyle,
+    pub icon_style: ContainerStyle,
+    pub text_style: ContainerStyle,
     pub binds: MouseBinds,
 }
 
@@ -608,6 +1350,9 @@ pub struct RawTime {
     #[knus(child, unwrap(argument), default = "%a %b %-d\n%-m/%-d/%y".to_string())]
     pub tooltip_format: String,
 
+    #[knus(child, unwrap(argument), default = true)]
+    pub show_tooltip: bool,
+
     #[knus(flatten(child), default)]
     pub binds: RawMouseBinds,
 
@@ -620,6 +1365,7 @@ impl RawTime {
         let time = Time {
             format: self.format,
             tooltip_format: self.tooltip_format,
+            show_tooltip: self.show_tooltip,
             binds: self.binds.hydrate(),
             style: self.style.hydrate(colors),
         };
@@ -631,6 +1377,7 @@ impl RawTime {
 pub struct Time {
     pub format: String,
     pub tooltip_format: String,
+    pub show_tooltip: bool,
     pub binds: MouseBinds,
     pub style: ContainerStyle,
 }
@@ -645,14 +1392,26 @@ pub struct RawMpris {
 
     #[knus(child, default)]
     pub placeholder_style: RawContainerStyle,
+
+    #[knus(child, unwrap(argument), default = false)]
+    pub controls_in_tooltip: bool,
+
+    #[knus(child, unwrap(argument), default = true)]
+    pub show_tooltip: bool,
+
+    #[knus(child, default)]
+    pub scroll_steps: RawScrollSteps,
 }
 
 impl RawMpris {
     fn hydrate(self, colors: &ColorVars) -> ConfigModule {
         let mpris = Mpris {
             placeholder: self.placeholder,
-            binds: self.binds.into(),
+            binds: self.binds.hydrate(&self.scroll_steps),
             placeholder_style: self.placeholder_style.hydrate(colors),
+            controls_in_tooltip: self.controls_in_tooltip,
+            show_tooltip: self.show_tooltip,
+            scroll_steps: self.scroll_steps,
         };
 
         ConfigModule::Mpris(mpris)
@@ -663,6 +1422,69 @@ pub struct Mpris {
     pub placeholder: String,
     pub binds: MouseBindsForMpris,
     pub placeholder_style: ContainerStyle,
+    pub controls_in_tooltip: bool,
+    pub show_tooltip: bool,
+    /// Kept for printing, the binds already have the steps applied
+    pub scroll_steps: RawScrollSteps,
+}
+
+#[derive(knus::Decode, Debug, Clone)]
+pub struct RawNowPlaying {
+    #[knus(child, unwrap(argument), default = "{artist} - {title}".to_string())]
+    pub format: String,
+
+    #[knus(child, unwrap(argument), default = 16)]
+    pub size: u32,
+
+    #[knus(child, unwrap(argument))]
+    pub max_width: Option<usize>,
+
+    #[knus(child, unwrap(argument), default = true)]
+    pub scroll: bool,
+
+    #[knus(child, unwrap(argument), default = 500)]
+    pub scroll_interval: u64,
+
+    #[knus(flatten(child), default)]
+    pub binds: RawMouseBindsForMpris,
+
+    #[knus(child, default)]
+    pub scroll_steps: RawScrollSteps,
+
+    #[knus(child, default)]
+    pub style: RawContainerStyle,
+}
+
+impl RawNowPlaying {
+    fn hydrate(self, colors: &ColorVars) -> ConfigModule {
+        let now_playing = NowPlaying {
+            format: self.format,
+            size: self.size,
+            max_width: self.max_width.filter(|w| *w > 0),
+            scroll: self.scroll,
+            scroll_interval: Duration::from_millis(
+                self.scroll_interval.max(50),
+            ),
+            binds: self.binds.hydrate(&self.scroll_steps),
+            scroll_steps: self.scroll_steps,
+            style: self.style.hydrate(colors),
+        };
+
+        ConfigModule::NowPlaying(now_playing)
+    }
+}
+
+pub struct NowPlaying {
+    pub format: String,
+    pub size: u32,
+    /// Longest text shown, in characters
+    pub max_width: Option<usize>,
+    pub scroll: bool,
+    pub scroll_interval: Duration,
+    pub binds: MouseBindsForMpris,
+    /// Kept for printing, the binds already have the steps applied
+    pub scroll_steps: RawScrollSteps,
+    pub style: ContainerStyle,
 }
 
 #[derive(knus::Decode, Debug, Clone)]
@@ -670,9 +1492,33 @@ pub struct RawNiri {
     #[knus(child, unwrap(argument), default = 10)]
     spacing: u32,
 
+    #[knus(child, unwrap(argument))]
+    window_spacing: Option<u32>,
+
+    #[knus(child, unwrap(argument))]
+    workspace_padding: Option<u32>,
+
     #[knus(child, unwrap(argument), default = 0)]
     workspace_offset: i8,
 
+    #[knus(child, unwrap(argument), default = true)]
+    track_layout: bool,
+
+    #[knus(child, unwrap(argument), default)]
+    window_sort: WindowSort,
+
+    #[knus(child, unwrap(argument))]
+    socket: Option<String>,
+
+    #[knus(child, unwrap(argument))]
+    output: Option<String>,
+
+    #[knus(child, unwrap(argument), default = false)]
+    drag_to_move: bool,
+
+    #[knus(child, unwrap(argument), default = true)]
+    show_tooltip: bool,
+
     #[knus(child, default)]
     style: RawContainerStyle,
 
@@ -688,6 +1534,9 @@ pub struct RawNiri {
     #[knus(child, default)]
     workspace_hovered_style: RawContainerStyle,
 
+    #[knus(child, default)]
+    workspace_active_hovered_style: RawContainerStyle,
+
     #[knus(child, default)]
     workspace_style: RawContainerStyle,
 
@@ -774,6 +1623,37 @@ impl RawNiri {
             workspace_active_hovered_style.border = active_hovered_border;
         }
 
+        if let Some(text_color) = &self
+            .workspace_active_hovered_style
+            .text_color
+            .map(|c| c.resolve(colors))
+        {
+            workspace_active_hovered_style.text_color = Some(*text_color);
+        }
+        if let Some(background) = &self
+            .workspace_active_hovered_style
+            .background
+            .map(|c| c.resolve(colors))
+        {
+            workspace_active_hovered_style.background =
+                Some(Background::Color(*background));
+        }
+        if let Some(border) = &self.workspace_active_hovered_style.border {
+            let mut active_hovered_border = iced::Border::default();
+            if let Some(color) =
+                border.color.as_ref().map(|c| c.resolve(colors))
+            {
+                active_hovered_border.color = color;
+            }
+            if let Some(width) = border.width {
+                active_hovered_border.width = width.into();
+            }
+            if let Some(radius) = &border.radius {
+                active_hovered_border.radius = radius.clone().into();
+            }
+            workspace_active_hovered_style.border = active_hovered_border;
+        }
+
         let workspace_active_hovered_style_merged =
             workspace_active_hovered_style;
         let workspace_active_style_merged = workspace_active_style;
@@ -819,7 +1699,15 @@ impl RawNiri {
 
         let niri = Niri {
             spacing: self.spacing,
+            window_spacing: self.window_spacing,
+            workspace_padding: self.workspace_padding,
             workspace_offset: self.workspace_offset,
+            track_layout: self.track_layout,
+            window_sort: self.window_sort,
+            socket: self.socket,
+            output: self.output,
+            drag_to_move: self.drag_to_move,
+            show_tooltip: self.show_tooltip,
             style: self.style.hydrate(colors),
             workspace_style: NiriWorkspaceStyle {
                 active_hovered: workspace_active_hovered_style_merged,
@@ -834,17 +1722,90 @@ impl RawNiri {
             binds: self.binds.hydrate(),
         };
 
-        ConfigModule::Niri(Box::new(niri))
+        ConfigModule::Niri(Box::new(niri))
+    }
+}
+
+/// Order of the window icons of a workspace
+#[derive(knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Default)]
+pub enum WindowSort {
+    /// Floating windows first, then by position in the scrolling layout.
+    /// Falls back to `Id` when the layout isn't tracked.
+    #[default]
+    Layout,
+    /// Oldest window first
+    Id,
+    /// Most recently focused window first
+    Focus,
+}
+
+pub struct Niri {
+    pub spacing: u32,
+    window_spacing: Option<u32>,
+    workspace_padding: Option<u32>,
+    pub workspace_offset: i8,
+    pub track_layout: bool,
+    pub window_sort: WindowSort,
+    /// Socket to use when `NIRI_SOCKET` isn't set
+    pub socket: Option<String>,
+    /// Only show the workspaces of this output
+    pub output: Option<String>,
+    /// Drag window icons onto another workspace to move the window there
+    pub drag_to_move: bool,
+    /// Show the title of a window when hovering its icon
+    pub show_tooltip: bool,
+    pub style: ContainerStyle,
+    pub workspace_style: NiriWorkspaceStyle,
+    pub window_style: NiriWindowStyle,
+    pub binds: MouseBinds,
+}
+
+impl Niri {
+    /// Space between the windows of a workspace. Defaults to 5 for
+    /// horizontal bars and nothing for vertical ones.
+    pub fn window_spacing(&self, vertical: bool) -> u32 {
+        self.window_spacing.unwrap_or(if vertical { 0 } else { 5 })
+    }
+
+    /// Space at both ends of a workspace, along the bar. Defaults to 3 for
+    /// vertical bars and 5 for horizontal ones.
+    pub fn workspace_padding(&self, vertical: bool) -> u32 {
+        self.workspace_padding
+            .unwrap_or(if vertical { 3 } else { 5 })
+    }
+}
+
+#[derive(knus::Decode, Debug)]
+pub struct RawColumnIndicator {
+    #[knus(child, unwrap(argument), default = "{column}/{columns}".to_string())]
+    pub format: String,
+
+    #[knus(child, unwrap(argument), default = 16)]
+    pub size: u32,
+
+    #[knus(flatten(child), default)]
+    pub binds: RawMouseBinds,
+
+    #[knus(child, default)]
+    pub style: RawContainerStyle,
+}
+
+impl RawColumnIndicator {
+    fn hydrate(self, colors: &ColorVars) -> ConfigModule {
+        ConfigModule::ColumnIndicator(ColumnIndicator {
+            format: self.format,
+            size: self.size,
+            binds: self.binds.hydrate(),
+            style: self.style.hydrate(colors),
+        })
     }
 }
 
-pub struct Niri {
-    pub spacing: u32,
-    pub workspace_offset: i8,
-    pub style: ContainerStyle,
-    pub workspace_style: NiriWorkspaceStyle,
-    pub window_style: NiriWindowStyle,
+pub struct ColumnIndicator {
+    pub format: String,
+    pub size: u32,
     pub binds: MouseBinds,
+    pub style: ContainerStyle,
 }
 
 pub struct NiriWorkspaceStyle {
@@ -854,11 +1815,31 @@ pub struct NiriWorkspaceStyle {
     pub base: ContainerStyle,
 }
 
+impl NiriWorkspaceStyle {
+    pub fn get(&self, active: bool, hovered: bool) -> &ContainerStyle {
+        if active && hovered {
+            &self.active_hovered
+        } else if active {
+            &self.active
+        } else if hovered {
+            &self.hovered
+        } else {
+            &self.base
+        }
+    }
+}
+
 pub struct NiriWindowStyle {
     pub focused: ContainerStyle,
     pub base: ContainerStyle,
 }
 
+impl NiriWindowStyle {
+    pub fn get(&self, focused: bool) -> &ContainerStyle {
+        if focused { &self.focused } else { &self.base }
+    }
+}
+
 #[derive(knus::Decode, Debug)]
 pub struct RawLabel {
     #[knus(child, unwrap(argument), default = String::new())]
@@ -867,9 +1848,21 @@ pub struct RawLabel {
     #[knus(child, unwrap(argument), default = 18)]
     pub size: u32,
 
+    #[knus(children(name = "line"))]
+    pub lines: Vec<RawLabelLine>,
+
     #[knus(child, unwrap(argument), default = None)]
     pub tooltip: Option<String>,
 
+    #[knus(child, unwrap(argument), default = true)]
+    pub show_tooltip: bool,
+
+    #[knus(child, unwrap(argument))]
+    pub hover_text_color: Option<ConfigColor>,
+
+    #[knus(child, unwrap(argument))]
+    pub watch_file: Option<String>,
+
     #[knus(flatten(child), default)]
     pub binds: RawMouseBinds,
 
@@ -877,12 +1870,39 @@ pub struct RawLabel {
     pub style: RawContainerStyle,
 }
 
+#[derive(knus::Decode, Debug)]
+pub struct RawLabelLine {
+    #[knus(child, unwrap(argument), default = String::new())]
+    pub text: String,
+
+    #[knus(child, unwrap(argument))]
+    pub size: Option<u32>,
+
+    #[knus(child, unwrap(argument))]
+    pub color: Option<ConfigColor>,
+}
+
 impl RawLabel {
     fn hydrate(self, colors: &ColorVars) -> ConfigModule {
+        let size = self.size;
         let label = Label {
             text: self.text,
-            size: self.size,
+            size,
+            lines: self
+                .lines
+                .into_iter()
+                .map(|line| LabelLine {
+                    text: line.text,
+                    size: line.size.unwrap_or(size),
+                    color: line.color.map(|color| color.resolve(colors)),
+                })
+                .collect(),
             tooltip: self.tooltip,
+            show_tooltip: self.show_tooltip,
+            hover_text_color: self
+                .hover_text_color
+                .map(|color| color.resolve(colors)),
+            watch_file: self.watch_file.map(PathBuf::from),
             binds: self.binds.hydrate(),
             style: self.style.hydrate(colors),
         };
@@ -894,7 +1914,129 @@ impl RawLabel {
 pub struct Label {
     pub text: String,
     pub size: u32,
+    pub lines: Vec<LabelLine>,
     pub tooltip: Option<String>,
+    pub show_tooltip: bool,
+    /// Text color to fade to while the pointer is over the label
+    pub hover_text_color: Option<Color>,
+    /// File whose contents are shown instead of the text, see
+    /// [`crate::file_watcher::watch_file`]
+    pub watch_file: Option<PathBuf>,
+    pub binds: MouseBinds,
+    pub style: ContainerStyle,
+}
+
+pub struct LabelLine {
+    pub text: String,
+    pub size: u32,
+    pub color: Option<Color>,
+}
+
+#[derive(knus::Decode, Debug)]
+pub struct RawSpacer {
+    #[knus(child, unwrap(argument), default = SpacerSize::Fixed(10.0))]
+    pub size: SpacerSize,
+}
+
+impl RawSpacer {
+    fn hydrate(self, _colors: &ColorVars) -> ConfigModule {
+        ConfigModule::Spacer(Spacer { size: self.size })
+    }
+}
+
+pub struct Spacer {
+    pub size: SpacerSize,
+}
+
+#[derive(knus::Decode, Debug)]
+pub struct RawGroup {
+    #[knus(child, unwrap(argument), default = "󰅁".to_string())]
+    pub icon: String,
+
+    #[knus(child, unwrap(argument), default = "󰅀".to_string())]
+    pub expanded_icon: String,
+
+    #[knus(child, unwrap(argument), default = 16)]
+    pub size: u32,
+
+    #[knus(child, unwrap(argument), default)]
+    pub expand_on: ExpandOn,
+
+    #[knus(child, unwrap(argument), default = false)]
+    pub expanded: bool,
+
+    #[knus(child, unwrap(argument))]
+    pub spacing: Option<FloatOrInt<0, { i32::MAX }>>,
+
+    #[knus(child, default)]
+    pub modules: RawGroupModules,
+
+    #[knus(flatten(child), default)]
+    pub binds: RawMouseBinds,
+
+    #[knus(child, default)]
+    pub style: RawContainerStyle,
+}
+
+#[derive(knus::Decode, Debug, Default)]
+pub struct RawGroupModules {
+    #[knus(children, default)]
+    pub modules: Vec<RawConfigModule>,
+}
+
+/// What opens and closes a group
+#[derive(knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Default)]
+pub enum ExpandOn {
+    /// Clicking the group's icon
+    #[default]
+    Click,
+    /// Moving the pointer over the group
+    Hover,
+}
+
+impl RawGroup {
+    fn hydrate(self, colors: &ColorVars) -> ConfigModule {
+        let modules = self
+            .modules
+            .modules
+            .into_iter()
+            .filter(|module| {
+                let nested = matches!(module, RawConfigModule::Group(_));
+                if nested {
+                    warn!("groups can't be nested, ignoring the inner group");
+                }
+                !nested
+            })
+            .map(|module| module.hydrate(colors))
+            .collect();
+
+        ConfigModule::Group(Group {
+            icon: self.icon,
+            expanded_icon: self.expanded_icon,
+            size: self.size,
+            expand_on: self.expand_on,
+            expanded: self.expanded,
+            spacing: self.spacing.map(Into::into),
+            modules,
+            binds: self.binds.hydrate(),
+            style: self.style.hydrate(colors),
+        })
+    }
+}
+
+pub struct Group {
+    /// Shown while the group is collapsed
+    pub icon: String,
+    pub expanded_icon: String,
+    pub size: u32,
+    pub expand_on: ExpandOn,
+    /// Whether the group starts out expanded
+    pub expanded: bool,
+    /// Space between the modules of the group, the layout's section spacing
+    /// if unset
+    pub spacing: Option<f32>,
+    /// In the order they're shown in
+    pub modules: Vec<ConfigModule>,
     pub binds: MouseBinds,
     pub style: ContainerStyle,
 }
@@ -910,7 +2052,7 @@ impl RawSystemTray {
 
 pub struct SystemTray {}
 
-#[derive(knus::Decode, Debug, Clone, Default)]
+#[derive(knus::Decode, Debug, Clone, Default, PartialEq)]
 pub struct RawMouseBinds {
     #[knus(child)]
     pub mouse_left: Option<Command>,
@@ -941,6 +2083,7 @@ impl RawMouseBinds {
     fn hydrate(self) -> MouseBinds {
         fn process_command(cmd: Option<Command>) -> Option<Message> {
             let cmd = cmd?;
+            let throttle = cmd.throttle.map(Duration::from_millis);
             if cmd.args.is_empty() {
                 None
             } else if let Some(sh) = cmd.sh
@@ -949,11 +2092,13 @@ impl RawMouseBinds {
                 Some(Message::Command(CommandSpec {
                     command: String::from("sh"),
                     args: Some(vec![String::from("-c"), cmd.args[0].clone()]),
+                    throttle,
                 }))
             } else {
                 Some(Message::Command(CommandSpec {
                     command: cmd.args[0].clone(),
                     args: cmd.args.get(1..).map(<[String]>::to_vec),
+                    throttle,
                 }))
             }
         }
@@ -1009,6 +2154,36 @@ pub enum RawMediaControl {
     Seek(i64),
     Volume(FloatOrInt<{ -i32::MAX }, { i32::MAX }>),
     SetVolume(FloatOrInt<0, { i32::MAX }>),
+    /// Named steps, sized by [`RawScrollSteps`]
+    VolumeUp,
+    VolumeDown,
+    SeekForward,
+    SeekBackward,
+}
+
+/// Step sizes of the named media controls, tuned for scrolling, where a
+/// control fires many times in a row
+#[derive(knus::Decode, Debug, Clone, Copy)]
+pub struct RawScrollSteps {
+    /// Volume change of `volume-up` and `volume-down`
+    #[knus(child, unwrap(argument), default = Self::default().volume)]
+    pub volume: FloatOrInt<0, 1>,
+    /// Milliseconds skipped by `seek-forward` and `seek-backward`
+    #[knus(child, unwrap(argument), default = Self::default().seek)]
+    pub seek: u32,
+    /// Volume that `volume-up` doesn't go beyond
+    #[knus(child, unwrap(argument), default = Self::default().max_volume)]
+    pub max_volume: FloatOrInt<0, { i32::MAX }>,
+}
+
+impl Default for RawScrollSteps {
+    fn default() -> Self {
+        Self {
+            volume: FloatOrInt(0.02),
+            seek: 5000,
+            max_volume: FloatOrInt(1.0),
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq)]
@@ -1022,11 +2197,19 @@ pub enum MediaControl {
     Seek(i64),
     Volume(f32),
     SetVolume(f32),
-}
-
-impl From<RawMediaControl> for MediaControl {
-    fn from(value: RawMediaControl) -> Self {
-        match value {
+    /// Changes the volume by `step`, staying within 0 and `max`
+    StepVolume {
+        step: f32,
+        max: f32,
+    },
+}
+
+impl RawMediaControl {
+    fn hydrate(self, steps: &RawScrollSteps) -> MediaControl {
+        // seek steps are in milliseconds, mpris seeks in microseconds
+        let seek = i64::from(steps.seek) * 1000;
+        let max = steps.max_volume.into();
+        match self {
             RawMediaControl::Play => MediaControl::Play,
             RawMediaControl::Pause => MediaControl::Pause,
             RawMediaControl::PlayPause => MediaControl::PlayPause,
@@ -1036,6 +2219,16 @@ impl From<RawMediaControl> for MediaControl {
             RawMediaControl::Seek(x) => MediaControl::Seek(x),
             RawMediaControl::Volume(x) => MediaControl::Volume(x.into()),
             RawMediaControl::SetVolume(x) => MediaControl::SetVolume(x.into()),
+            RawMediaControl::VolumeUp => MediaControl::StepVolume {
+                step: steps.volume.into(),
+                max,
+            },
+            RawMediaControl::VolumeDown => MediaControl::StepVolume {
+                step: -f32::from(steps.volume),
+                max,
+            },
+            RawMediaControl::SeekForward => MediaControl::Seek(seek),
+            RawMediaControl::SeekBackward => MediaControl::Seek(-seek),
         }
     }
 }
@@ -1092,6 +2285,10 @@ where
                 "stop" => Ok(RawMediaControl::Stop),
                 "next" => Ok(RawMediaControl::Next),
                 "previous" => Ok(RawMediaControl::Previous),
+                "volume-up" => Ok(RawMediaControl::VolumeUp),
+                "volume-down" => Ok(RawMediaControl::VolumeDown),
+                "seek-forward" => Ok(RawMediaControl::SeekForward),
+                "seek-backward" => Ok(RawMediaControl::SeekBackward),
                 "seek" => {
                     let Some(second_arg) = iter_args.next() else {
                         return Err(DecodeError::missing(
@@ -1147,7 +2344,7 @@ where
                 }
                 _other => Err(DecodeError::unsupported(
                     &first_arg.literal,
-                    "expected `play`, `pause`, `play-pause`, `stop`, `next`, `previous`, `seek`, `volume`, or `set-volume`",
+                    "expected `play`, `pause`, `play-pause`, `stop`, `next`, `previous`, `seek`, `volume`, `set-volume`, `volume-up`, `volume-down`, `seek-forward`, or `seek-backward`",
                 )),
             },
             _other => {
@@ -1195,17 +2392,20 @@ pub struct MouseBindsForMpris {
     pub scroll_left: Option<MediaControl>,
 }
 
-impl From<RawMouseBindsForMpris> for MouseBindsForMpris {
-    fn from(other: RawMouseBindsForMpris) -> Self {
-        Self {
-            mouse_left: other.mouse_left.map(Into::into),
-            double_click: other.double_click.map(Into::into),
-            mouse_right: other.mouse_right.map(Into::into),
-            mouse_middle: other.mouse_middle.map(Into::into),
-            scroll_up: other.scroll_up.map(Into::into),
-            scroll_down: other.scroll_down.map(Into::into),
-            scroll_right: other.scroll_right.map(Into::into),
-            scroll_left: other.scroll_left.map(Into::into),
+impl RawMouseBindsForMpris {
+    fn hydrate(self, steps: &RawScrollSteps) -> MouseBindsForMpris {
+        let hydrate = |control: Option<RawMediaControl>| {
+            control.map(|control| control.hydrate(steps))
+        };
+        MouseBindsForMpris {
+            mouse_left: hydrate(self.mouse_left),
+            double_click: hydrate(self.double_click),
+            mouse_right: hydrate(self.mouse_right),
+            mouse_middle: hydrate(self.mouse_middle),
+            scroll_up: hydrate(self.scroll_up),
+            scroll_down: hydrate(self.scroll_down),
+            scroll_right: hydrate(self.scroll_right),
+            scroll_left: hydrate(self.scroll_left),
         }
     }
 }
@@ -1214,6 +2414,9 @@ impl From<RawMouseBindsForMpris> for MouseBindsForMpris {
 pub struct Command {
     pub sh: Option<bool>,
 
+    /// Minimum time between two spawns of the command, in milliseconds
+    pub throttle: Option<u64>,
+
     pub args: Vec<String>,
 }
 
@@ -1227,6 +2430,10 @@ pub struct RawContainerStyle {
     pub border: Option<ConfigBorder>,
     #[knus(child, unwrap(argument))]
     pub padding: Option<FloatOrInt<0, { i32::MAX }>>,
+    #[knus(child, unwrap(argument))]
+    pub text_shaping: Option<TextShaping>,
+    #[knus(child, unwrap(argument))]
+    pub margin: Option<FloatOrInt<0, { i32::MAX }>>,
 }
 
 impl RawContainerStyle {
@@ -1244,6 +2451,8 @@ impl RawContainerStyle {
                 ..Default::default()
             },
             padding: self.padding.map(Into::into),
+            text_shaping: self.text_shaping.unwrap_or_default(),
+            margin: self.margin.map_or(0.0, Into::into),
         }
     }
 }
@@ -1252,6 +2461,45 @@ impl RawContainerStyle {
 pub struct ContainerStyle {
     pub inner: container::Style,
     pub padding: Option<f32>,
+    pub text_shaping: TextShaping,
+    /// Space kept around the module, collapsed with the margins of its
+    /// neighbours and the section spacing
+    pub margin: f32,
+}
+
+impl ContainerStyle {
+    /// Content has to be clipped to stay inside rounded corners
+    pub fn has_radius(&self) -> bool {
+        self.inner.border.radius != border::Radius::default()
+    }
+}
+
+/// Where a module's icon goes relative to its text
+#[derive(knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Default)]
+pub enum IconPosition {
+    /// Left of the text on horizontal bars, above it on vertical ones
+    #[default]
+    Before,
+    /// Right of the text on horizontal bars, below it on vertical ones
+    After,
+    Above,
+    Below,
+}
+
+#[derive(knus::DecodeScalar, Debug, Clone, Copy, PartialEq, Default)]
+pub enum TextShaping {
+    Basic,
+    #[default]
+    Advanced,
+}
+
+impl From<TextShaping> for Shaping {
+    fn from(value: TextShaping) -> Self {
+        match value {
+            TextShaping::Basic => Shaping::Basic,
+            TextShaping::Advanced => Shaping::Advanced,
+        }
+    }
 }
 
 impl Deref for ContainerStyle {
@@ -1403,6 +2651,53 @@ where
     }
 }
 
+impl<S> knus::Decode<S> for Gaps
+where
+    S: knus::traits::ErrorSpan,
+{
+    fn decode_node(
+        node: &knus::ast::SpannedNode<S>,
+        ctx: &mut knus::decode::Context<S>,
+    ) -> Result<Self, DecodeError<S>> {
+        if let Some(type_name) = &node.type_name {
+            ctx.emit_error(DecodeError::unexpected(
+                type_name,
+                "type name",
+                "no type name expected for this node",
+            ));
+        }
+
+        node.properties.iter().for_each(|property| {
+            ctx.emit_error(DecodeError::unexpected(
+                property.0,
+                "property",
+                "no propertes expected for this node",
+            ));
+        });
+
+        let mut iter_args = node.arguments.iter();
+        let Some(gaps) = iter_args.next() else {
+            return PerSide::decode_node(node, ctx).map(Into::into);
+        };
+
+        iter_args.for_each(|arg| {
+            ctx.emit_error(DecodeError::unexpected(
+                &arg.literal,
+                "argument",
+                "expected 1 or 0 arguments",
+            ));
+        });
+        node.children.iter().for_each(|child| {
+            ctx.emit_error(DecodeError::unexpected(
+                child,
+                "node",
+                "no children expected when gaps are specified as an argument",
+            ));
+        });
+        Ok(Self::splat(i32::decode(gaps, ctx)?))
+    }
+}
+
 impl<S> knus::Decode<S> for Command
 where
     S: knus::traits::ErrorSpan,
@@ -1420,11 +2715,15 @@ where
         }
 
         let mut sh = None;
+        let mut throttle = None;
         for (name, val) in &node.properties {
             match &***name {
                 "sh" => {
                     sh = knus::traits::DecodeScalar::decode(val, ctx)?;
                 }
+                "throttle" => {
+                    throttle = knus::traits::DecodeScalar::decode(val, ctx)?;
+                }
                 name_str => {
                     return Err(DecodeError::unexpected(
                         name,
@@ -1461,7 +2760,7 @@ where
                 format!("unexpected node `{0}`", name_str.escape_default(),),
             ));
         }
-        Ok(Command { sh, args })
+        Ok(Command { sh, throttle, args })
     }
 }
 
@@ -1524,16 +2823,22 @@ impl RawConfig {
         }
     }
 
-    pub fn create(path: &Path) -> miette::Result<()> {
+    /// Writes the default config to `path` if it doesn't exist yet. Returns
+    /// `false` if the config location isn't writable, e.g. on immutable
+    /// systems, in which case nothing is written.
+    pub fn create(path: &Path) -> miette::Result<bool> {
         if let Some(default_parent) = path.parent() {
-            fs::create_dir_all(default_parent)
-                .into_diagnostic()
-                .with_context(|| {
-                    format!(
-                        "error creating config directory {}",
-                        default_parent.display()
-                    )
-                })?;
+            match fs::create_dir_all(default_parent) {
+                Err(e) if is_unwritable(&e) => return Ok(false),
+                res => res,
+            }
+            .into_diagnostic()
+            .with_context(|| {
+                format!(
+                    "error creating config directory {}",
+                    default_parent.display()
+                )
+            })?;
         }
 
         let mut new_file = match File::options()
@@ -1543,8 +2848,9 @@ impl RawConfig {
             .open(path)
         {
             Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => {
-                return Ok(());
+                return Ok(true);
             }
+            Err(e) if is_unwritable(&e) => return Ok(false),
             res => res,
         }
         .into_diagnostic()
@@ -1564,12 +2870,72 @@ impl RawConfig {
                 format!("error writing default config to {}", path.display())
             })?;
 
-        Ok(())
+        Ok(true)
+    }
+
+    /// Writes the default config to the config path, even if a config already
+    /// exists. An existing config is first moved to `config.kdl.bak`, whose
+    /// path is returned.
+    pub fn reset(
+        config_dir: Option<PathBuf>,
+    ) -> miette::Result<(PathBuf, Option<PathBuf>)> {
+        let (config_path, _) = get_config_paths(config_dir);
+
+        // a dangling symlink doesn't exist to `Path::exists`, but it still
+        // takes up the path, e.g. a config managed by stow or home-manager
+        let backup = if fs::symlink_metadata(&config_path).is_ok() {
+            let backup_path = config_path.with_extension("kdl.bak");
+            fs::rename(&config_path, &backup_path)
+                .into_diagnostic()
+                .with_context(|| {
+                    format!(
+                        "error backing up {} to {}",
+                        config_path.display(),
+                        backup_path.display()
+                    )
+                })?;
+            Some(backup_path)
+        } else {
+            None
+        };
+
+        if !RawConfig::create(&config_path)? {
+            miette::bail!(
+                "config location {} is not writable",
+                config_path.display()
+            );
+        }
+
+        Ok((config_path, backup))
     }
 
     pub fn load_or_create(path: &Path) -> miette::Result<Self> {
-        RawConfig::create(path)?;
-        RawConfig::load(path)
+        if RawConfig::create(path)? {
+            RawConfig::load(path)
+        } else {
+            warn!(
+                "Config file {} does not exist and cannot be created, using built-in default config",
+                path.display()
+            );
+            Ok(RawConfig::default())
+        }
+    }
+
+    /// Print the config with defaults filled in and colors resolved. Unlike
+    /// the bar itself this never falls back to the default config, so errors
+    /// are reported instead.
+    pub fn print_resolved(config_dir: Option<PathBuf>) -> miette::Result<()> {
+        let (config_path, colors_path) = get_config_paths(config_dir);
+
+        let colors = if colors_path.exists() {
+            ColorVars::load(&colors_path)?
+        } else {
+            ColorVars::default()
+        };
+
+        let config = RawConfig::load(&config_path)?.hydrate(&colors);
+        print!("{config}");
+        Ok(())
     }
 
     pub fn validate(config_dir: Option<PathBuf>) {
@@ -1633,16 +2999,32 @@ impl RawConfig {
     }
 }
 
+fn is_unwritable(e: &std::io::Error) -> bool {
+    matches!(
+        e.kind(),
+        std::io::ErrorKind::PermissionDenied
+            | std::io::ErrorKind::ReadOnlyFilesystem
+    )
+}
+
+/// The config directory is taken from the cli flag, then
+/// `FROSTBAR_CONFIG_DIR`, then the XDG default
 fn get_config_paths(config_dir: Option<PathBuf>) -> (PathBuf, PathBuf) {
-    let config_dir = config_dir.unwrap_or_else(|| {
-        let home = if let Ok(xdg_config_home) = std::env::var("XDG_CONFIG_HOME")
-        {
-            PathBuf::from(xdg_config_home)
-        } else {
-            std::env::home_dir().unwrap()
-        };
-        home.join(".config").join("frostbar")
-    });
+    let config_dir = config_dir
+        .or_else(|| {
+            std::env::var_os(CONFIG_DIR_ENV)
+                .filter(|dir| !dir.is_empty())
+                .map(PathBuf::from)
+        })
+        .unwrap_or_else(|| {
+            let home =
+                if let Ok(xdg_config_home) = std::env::var("XDG_CONFIG_HOME") {
+                    PathBuf::from(xdg_config_home)
+                } else {
+                    std::env::home_dir().unwrap()
+                };
+            home.join(".config").join("frostbar")
+        });
 
     let config_path = config_dir.join("config.kdl");
     let colors_path = config_dir.join("colors.kdl");
@@ -1749,4 +3131,173 @@ mod tests {
             panic!("{e}")
         }
     }
+
+    #[test]
+    fn vertical_bars_yield_shared_corners() {
+        let other_bars = Gaps {
+            top: 40,
+            left: 30,
+            ..Gaps::default()
+        };
+        let left = Layout {
+            gaps: Gaps::splat(0),
+            other_bars,
+            ..Layout::default()
+        };
+        let top = Layout {
+            anchor: Anchor::Top,
+            ..left.clone()
+        };
+        assert_eq!(left.margin(), (40, 0, 0, 0));
+        assert_eq!(top.margin(), (0, 0, 0, 0));
+
+        let left = Layout {
+            corners: Corners::Own,
+            ..left
+        };
+        let top = Layout {
+            corners: Corners::Yield,
+            ..top
+        };
+        assert_eq!(left.margin(), (0, 0, 0, 0));
+        assert_eq!(top.margin(), (0, 0, 0, 30));
+    }
+
+    #[test]
+    fn one_error_per_extra_gaps_argument() {
+        use miette::Diagnostic;
+
+        let err =
+            knus::parse::<RawConfig>("", "layout { gaps 1 2 3; }").unwrap_err();
+        assert_eq!(err.related().map_or(0, Iterator::count), 2);
+    }
+
+    #[test]
+    fn printed_binds_parse_back_the_same() {
+        let hydrate = |text: &str| {
+            RawConfig::parse("", text)
+                .unwrap()
+                .hydrate(&ColorVars::default())
+        };
+        let config = hydrate(
+            r#"layout {
+                mouse-left "notify-send" "a b" throttle=500
+                mouse-right "echo $HOME" sh=true
+            }"#,
+        );
+
+        let printed = config.to_string();
+        let binds: String = printed
+            .lines()
+            .filter(|line| line.trim_start().starts_with("mouse-"))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let reparsed = hydrate(&format!("layout {{\n{binds}\n}}"));
+
+        assert_eq!(
+            format!("{:?}", reparsed.bar_binds),
+            format!("{:?}", config.bar_binds)
+        );
+    }
+
+    /// An empty directory of its own for each test
+    fn test_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("frostbar-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    #[test]
+    fn reset_backs_up_the_old_config() {
+        let dir = test_dir("reset");
+        let old = "layout { width 30; }";
+        fs::write(dir.join("config.kdl"), old).unwrap();
+
+        let (path, backup) = RawConfig::reset(Some(dir.clone())).unwrap();
+        let written = fs::read(&path).unwrap();
+        let backed_up = backup.map(|b| fs::read_to_string(b).unwrap());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(written, DEFAULT_CONFIG);
+        assert_eq!(backed_up.as_deref(), Some(old));
+    }
+
+    #[test]
+    fn reset_replaces_a_dangling_symlink() {
+        let dir = test_dir("reset-symlink");
+        let link = dir.join("config.kdl");
+        std::os::unix::fs::symlink(dir.join("missing.kdl"), &link).unwrap();
+
+        let (path, backup) = RawConfig::reset(Some(dir.clone())).unwrap();
+        let written = fs::read(&path).unwrap();
+        let backed_up_link = backup
+            .map(|b| fs::symlink_metadata(b).unwrap().file_type().is_symlink());
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(written, DEFAULT_CONFIG);
+        assert_eq!(backed_up_link, Some(true));
+    }
+
+    #[test]
+    fn printed_default_config_parses() {
+        let config = RawConfig::parse("", "")
+            .unwrap()
+            .hydrate(&ColorVars::default());
+        let printed = config.to_string();
+
+        if let Err(e) = RawConfig::parse("", &printed) {
+            panic!("{e:?}\n{printed}")
+        }
+    }
+
+    #[test]
+    fn printed_scroll_steps_parse_back_the_same() {
+        let print = |text: &str| {
+            let printed = RawConfig::parse("", text)
+                .unwrap()
+                .hydrate(&ColorVars::default())
+                .to_string();
+            // `end` is printed last, so it runs to the end of the output
+            let start = printed.find("\nend {").unwrap();
+            printed[start..].to_string()
+        };
+        let printed = print(
+            r#"end {
+                mpris {
+                    scroll-up "volume-up"
+                    scroll-down "volume-down"
+                    scroll-steps {
+                        volume 0.05
+                        max-volume 1.5
+                    }
+                }
+            }"#,
+        );
+
+        assert!(printed.contains("scroll-up \"volume-up\""));
+        assert!(printed.contains("scroll-down \"volume-down\""));
+        assert_eq!(print(&printed), printed);
+    }
+
+    #[test]
+    fn scale_modes_on_scaled_outputs() {
+        for (output, physical_width) in [(1.0, 42), (1.25, 34), (2.0, 21)] {
+            let mut layout = Layout {
+                anchor: Anchor::Top,
+                ..Layout::default()
+            };
+            layout.scale.output = output;
+            assert_eq!(layout.surface_size(), (0, 42), "logical at {output}");
+
+            layout.scale_mode = ScaleMode::Physical;
+            assert_eq!(
+                layout.surface_size(),
+                (0, physical_width),
+                "physical at {output}"
+            );
+            assert!((layout.factor() * output - 1.0).abs() < 1e-6);
+        }
+    }
 }
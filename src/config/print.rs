@@ -0,0 +1,484 @@
+//! Printing of the hydrated config, for `--print-config`. The output is KDL
+//! in the same shape as the config file, with defaults filled in and color
+//! variables resolved.
+
+use std::fmt::{self, Display, Formatter};
+
+use iced::{Border, Color, border::Radius};
+
+use super::{
+    Config, ConfigModule, ContainerStyle, FloatOrPercent, FollowPlayer,
+    MediaControl, MouseBinds, MouseBindsForMpris, RawScrollSteps, SpacerSize,
+};
+use crate::{CommandSpec, Message, modules::BarAlignment};
+
+struct Printer<'a, 'b> {
+    f: &'a mut Formatter<'b>,
+    depth: usize,
+}
+
+impl Printer<'_, '_> {
+    fn value(&mut self, name: &str, value: impl Display) -> fmt::Result {
+        writeln!(self.f, "{:1$}{name} {value}", "", self.depth * 4)
+    }
+
+    fn block(
+        &mut self,
+        name: &str,
+        body: impl FnOnce(&mut Self) -> fmt::Result,
+    ) -> fmt::Result {
+        writeln!(self.f, "{:1$}{name} {{", "", self.depth * 4)?;
+        self.depth += 1;
+        body(self)?;
+        self.depth -= 1;
+        writeln!(self.f, "{:1$}}}", "", self.depth * 4)
+    }
+
+    fn style(&mut self, name: &str, style: &ContainerStyle) -> fmt::Result {
+        self.block(name, |p| {
+            if let Some(color) = style.inner.text_color {
+                p.value("text-color", Hex(color))?;
+            }
+            if let Some(iced::Background::Color(color)) = style.inner.background
+            {
+                p.value("background", Hex(color))?;
+            }
+            if let Some(padding) = style.padding {
+                p.value("padding", padding)?;
+            }
+            p.value("margin", style.margin)?;
+            p.value("text-shaping", Variant(style.text_shaping))?;
+            p.border(&style.inner.border)
+        })
+    }
+
+    fn border(&mut self, border: &Border) -> fmt::Result {
+        self.block("border", |p| {
+            p.value("color", Hex(border.color))?;
+            p.value("width", border.width)?;
+            p.radius("radius", &border.radius)
+        })
+    }
+
+    fn radius(&mut self, name: &str, radius: &Radius) -> fmt::Result {
+        self.block(name, |p| {
+            p.value("top-left", radius.top_left)?;
+            p.value("top-right", radius.top_right)?;
+            p.value("bottom-left", radius.bottom_left)?;
+            p.value("bottom-right", radius.bottom_right)
+        })
+    }
+
+    fn binds(&mut self, binds: &MouseBinds) -> fmt::Result {
+        let scroll = binds.scroll.as_ref();
+        for (name, bind) in [
+            ("mouse-left", binds.mouse_left.as_ref()),
+            ("double-click", binds.double_click.as_ref()),
+            ("mouse-right", binds.mouse_right.as_ref()),
+            ("mouse-middle", binds.mouse_middle.as_ref()),
+            ("scroll-up", scroll.and_then(|s| s.up.as_ref())),
+            ("scroll-down", scroll.and_then(|s| s.down.as_ref())),
+            ("scroll-left", scroll.and_then(|s| s.left.as_ref())),
+            ("scroll-right", scroll.and_then(|s| s.right.as_ref())),
+        ] {
+            // binds from the config file are always commands
+            if let Some(Message::Command(cmd)) = bind {
+                self.value(name, Bind(cmd))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn media_binds(&mut self, binds: &MouseBindsForMpris) -> fmt::Result {
+        for (name, bind) in [
+            ("mouse-left", binds.mouse_left),
+            ("double-click", binds.double_click),
+            ("mouse-right", binds.mouse_right),
+            ("mouse-middle", binds.mouse_middle),
+            ("scroll-up", binds.scroll_up),
+            ("scroll-down", binds.scroll_down),
+            ("scroll-left", binds.scroll_left),
+            ("scroll-right", binds.scroll_right),
+        ] {
+            if let Some(control) = bind {
+                self.value(name, Control(control))?;
+            }
+        }
+        Ok(())
+    }
+
+    fn scroll_steps(&mut self, steps: &RawScrollSteps) -> fmt::Result {
+        self.block("scroll-steps", |p| {
+            p.value("volume", f32::from(steps.volume))?;
+            p.value("seek", steps.seek)?;
+            p.value("max-volume", f32::from(steps.max_volume))
+        })
+    }
+
+    fn module(&mut self, module: &ConfigModule) -> fmt::Result {
+        match module {
+            ConfigModule::AudioVisualizer(c) => {
+                self.block("audio-visualizer", |p| {
+                    p.value("length", c.length)?;
+                    p.value("spacing", Size(c.spacing))?;
+                    p.value("color", Hex(c.color))?;
+                    p.value("dynamic-color", c.dynamic_color)?;
+                    p.value("grow", Variant(c.grow))?;
+                    p.value(
+                        "follow-player",
+                        Str(match &c.follow_player {
+                            FollowPlayer::Active => "active",
+                            FollowPlayer::Named(name) => name,
+                        }),
+                    )?;
+                    p.binds(&c.binds)?;
+                    p.style("style", &c.style)
+                })
+            }
+            ConfigModule::Battery(c) => self.block("battery", |p| {
+                p.value("icon-size", c.icon_size)?;
+                p.value("charging-color", Hex(c.charging_color))?;
+                p.value("interval", c.interval.as_secs())?;
+                p.value("show-percentage", c.show_percentage)?;
+                p.value("text-size", c.text_size)?;
+                p.value("icon-position", Variant(c.icon_position))?;
+                p.value("precision", c.precision)?;
+                if let Some(step) = c.round_to {
+                    p.value("round-to", step)?;
+                }
+                p.value("smoothing", c.smoothing)?;
+                p.value("show-tooltip", c.show_tooltip)?;
+                p.value("tooltip-format", Str(&c.tooltip_format))?;
+                p.binds(&c.binds)?;
+                p.style("style", &c.style)?;
+                p.style("icon-style", &c.icon_style)?;
+                p.style("text-style", &c.text_style)
+            }),
+            ConfigModule::ColumnIndicator(c) => {
+                self.block("column-indicator", |p| {
+                    p.value("format", Str(&c.format))?;
+                    p.value("size", c.size)?;
+                    p.binds(&c.binds)?;
+                    p.style("style", &c.style)
+                })
+            }
+            ConfigModule::Time(c) => self.block("time", |p| {
+                p.value("format", Str(&c.format))?;
+                p.value("tooltip-format", Str(&c.tooltip_format))?;
+                p.value("show-tooltip", c.show_tooltip)?;
+                p.binds(&c.binds)?;
+                p.style("style", &c.style)
+            }),
+            ConfigModule::Mpris(c) => self.block("mpris", |p| {
+                p.value("placeholder", Str(&c.placeholder))?;
+                p.value("controls-in-tooltip", c.controls_in_tooltip)?;
+                p.value("show-tooltip", c.show_tooltip)?;
+                p.media_binds(&c.binds)?;
+                p.scroll_steps(&c.scroll_steps)?;
+                p.style("placeholder-style", &c.placeholder_style)
+            }),
+            ConfigModule::NowPlaying(c) => self.block("now-playing", |p| {
+                p.value("format", Str(&c.format))?;
+                p.value("size", c.size)?;
+                if let Some(max_width) = c.max_width {
+                    p.value("max-width", max_width)?;
+                }
+                p.value("scroll", c.scroll)?;
+                p.value("scroll-interval", c.scroll_interval.as_millis())?;
+                p.media_binds(&c.binds)?;
+                p.scroll_steps(&c.scroll_steps)?;
+                p.style("style", &c.style)
+            }),
+            ConfigModule::Niri(c) => self.block("niri", |p| {
+                p.value("spacing", c.spacing)?;
+                if let Some(spacing) = c.window_spacing {
+                    p.value("window-spacing", spacing)?;
+                }
+                if let Some(padding) = c.workspace_padding {
+                    p.value("workspace-padding", padding)?;
+                }
+                p.value("workspace-offset", c.workspace_offset)?;
+                p.value("track-layout", c.track_layout)?;
+                p.value("window-sort", Variant(c.window_sort))?;
+                if let Some(socket) = &c.socket {
+                    p.value("socket", Str(socket))?;
+                }
+                if let Some(output) = &c.output {
+                    p.value("output", Str(output))?;
+                }
+                p.value("drag-to-move", c.drag_to_move)?;
+                p.value("show-tooltip", c.show_tooltip)?;
+                p.binds(&c.binds)?;
+                p.style("style", &c.style)?;
+                p.style("workspace-style", &c.workspace_style.base)?;
+                p.style("workspace-active-style", &c.workspace_style.active)?;
+                p.style("workspace-hovered-style", &c.workspace_style.hovered)?;
+                p.style(
+                    "workspace-active-hovered-style",
+                    &c.workspace_style.active_hovered,
+                )?;
+                p.style("window-style", &c.window_style.base)?;
+                p.style("window-focused-style", &c.window_style.focused)
+            }),
+            ConfigModule::Label(c) => self.block("label", |p| {
+                p.value("text", Str(&c.text))?;
+                p.value("size", c.size)?;
+                for line in &c.lines {
+                    p.block("line", |p| {
+                        p.value("text", Str(&line.text))?;
+                        p.value("size", line.size)?;
+                        if let Some(color) = line.color {
+                            p.value("color", Hex(color))?;
+                        }
+                        Ok(())
+                    })?;
+                }
+                if let Some(tooltip) = &c.tooltip {
+                    p.value("tooltip", Str(tooltip))?;
+                }
+                p.value("show-tooltip", c.show_tooltip)?;
+                if let Some(color) = c.hover_text_color {
+                    p.value("hover-text-color", Hex(color))?;
+                }
+                if let Some(path) = &c.watch_file {
+                    p.value("watch-file", Str(&path.to_string_lossy()))?;
+                }
+                p.binds(&c.binds)?;
+                p.style("style", &c.style)
+            }),
+            ConfigModule::Spacer(c) => self.block("spacer", |p| match c.size {
+                SpacerSize::Fixed(size) => p.value("size", size),
+                SpacerSize::Fill => p.value("size", Str("fill")),
+            }),
+            ConfigModule::SystemTray(_) => {
+                self.block("system-tray", |_| Ok(()))
+            }
+            ConfigModule::Group(c) => self.block("group", |p| {
+                p.value("icon", Str(&c.icon))?;
+                p.value("expanded-icon", Str(&c.expanded_icon))?;
+                p.value("size", c.size)?;
+                p.value("expand-on", Variant(c.expand_on))?;
+                p.value("expanded", c.expanded)?;
+                if let Some(spacing) = c.spacing {
+                    p.value("spacing", spacing)?;
+                }
+                p.block("modules", |p| {
+                    c.modules.iter().try_for_each(|m| p.module(m))
+                })?;
+                p.binds(&c.binds)?;
+                p.style("style", &c.style)
+            }),
+        }
+    }
+}
+
+/// A quoted, escaped string
+struct Str<'a>(&'a str);
+
+impl Display for Str<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?}", self.0)
+    }
+}
+
+/// A bind command as the arguments and properties of its node
+struct Bind<'a>(&'a CommandSpec);
+
+impl Display for Bind<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let cmd = self.0;
+        match cmd.args.as_deref().unwrap_or_default() {
+            [flag, script] if cmd.command == "sh" && flag == "-c" => {
+                write!(f, "{} sh=true", Str(script))?;
+            }
+            args => {
+                write!(f, "{}", Str(&cmd.command))?;
+                for arg in args {
+                    write!(f, " {}", Str(arg))?;
+                }
+            }
+        }
+        if let Some(throttle) = cmd.throttle {
+            write!(f, " throttle={}", throttle.as_millis())?;
+        }
+        Ok(())
+    }
+}
+
+/// A unit enum variant, which the config spells in kebab case
+struct Variant<T>(T);
+
+impl<T: fmt::Debug> Display for Variant<T> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = format!("{:?}", self.0);
+        let mut kebab = String::with_capacity(name.len() + 2);
+        for (i, c) in name.char_indices() {
+            if c.is_uppercase() && i > 0 {
+                kebab.push('-');
+            }
+            kebab.extend(c.to_lowercase());
+        }
+        write!(f, "{kebab:?}")
+    }
+}
+
+/// A color as `"#rrggbbaa"`
+struct Hex(Color);
+
+impl Display for Hex {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let [r, g, b, a] = self.0.into_rgba8();
+        write!(f, "\"#{r:02x}{g:02x}{b:02x}{a:02x}\"")
+    }
+}
+
+struct Size(FloatOrPercent);
+
+impl Display for Size {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            FloatOrPercent::Float(x) => write!(f, "{x}"),
+            FloatOrPercent::Percent(x) => write!(f, "\"{}%\"", x * 100.0),
+        }
+    }
+}
+
+struct Control(MediaControl);
+
+impl Display for Control {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            // mpris seeks in microseconds, the config in milliseconds
+            MediaControl::Seek(us) => write!(f, "\"seek\" {}", us / 1000),
+            MediaControl::Volume(x) => write!(f, "\"volume\" {x}"),
+            MediaControl::SetVolume(x) => write!(f, "\"set-volume\" {x}"),
+            // the step sizes are printed in `scroll-steps`
+            MediaControl::StepVolume { step, .. } if step > 0.0 => {
+                write!(f, "\"volume-up\"")
+            }
+            MediaControl::StepVolume { .. } => write!(f, "\"volume-down\""),
+            control => write!(f, "{}", Variant(control)),
+        }
+    }
+}
+
+impl Display for Config {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let mut p = Printer { f, depth: 0 };
+        let layout = &self.layout;
+
+        p.block("layout", |p| {
+            p.value("width", layout.width)?;
+            p.value("scale", layout.scale.factor)?;
+            p.value("scale-mode", Variant(layout.scale_mode))?;
+            p.block("gaps", |p| {
+                p.value("top", layout.gaps.top)?;
+                p.value("right", layout.gaps.right)?;
+                p.value("bottom", layout.gaps.bottom)?;
+                p.value("left", layout.gaps.left)
+            })?;
+            p.block("other-bars", |p| {
+                p.value("top", layout.other_bars.top)?;
+                p.value("right", layout.other_bars.right)?;
+                p.value("bottom", layout.other_bars.bottom)?;
+                p.value("left", layout.other_bars.left)
+            })?;
+            p.value("corners", Variant(layout.corners))?;
+            p.value("anchor", Variant(layout.anchor))?;
+            p.value("layer", Variant(layout.layer))?;
+            p.value(
+                "keyboard-interactivity",
+                Variant(layout.keyboard_interactivity),
+            )?;
+            p.value("middle-mode", Variant(layout.middle_mode))?;
+            p.value("when-empty", Variant(layout.when_empty))?;
+            if let Some(threshold) = layout.compact_below {
+                p.value("compact-below", threshold)?;
+            }
+            p.value("section-spacing", layout.section_spacing())?;
+            p.value(
+                "screen-corner-radius",
+                f32::from(layout.screen_corner_radius),
+            )?;
+            if let Some(region) = layout.avoid_region {
+                p.block("avoid-region", |p| {
+                    p.value("x", f32::from(region.x))?;
+                    p.value("width", f32::from(region.width))
+                })?;
+            }
+            p.binds(&self.bar_binds)
+        })?;
+
+        p.block("style", |p| {
+            p.value("background", Hex(self.style.background))?;
+            if let iced::font::Family::Name(family) =
+                self.style.icon_font.family
+            {
+                p.value("icon-font", Str(family))?;
+            }
+            for file in &self.style.font_files {
+                p.value("font-file", Str(&file.to_string_lossy()))?;
+            }
+            p.radius("border-radius", &self.style.border_radius)
+        })?;
+
+        p.block("gestures", |p| {
+            p.value("scroll-threshold", self.gestures.scroll_threshold)?;
+            p.value(
+                "scroll-timeout",
+                self.gestures.scroll_timeout.as_millis(),
+            )?;
+            p.value(
+                "click-suppression",
+                self.gestures.click_suppression.as_millis(),
+            )
+        })?;
+
+        p.block("icons", |p| {
+            for o in &self.icons.overrides {
+                p.value(
+                    "override",
+                    format!("{} {}", Str(&o.app_id), Str(&o.icon)),
+                )?;
+            }
+            if let Some(fallback) = &self.icons.fallback {
+                p.value("fallback", Str(fallback))?;
+            }
+            Ok(())
+        })?;
+
+        p.block("notifications", |p| {
+            p.value("enabled", self.notifications.enabled)?;
+            p.value("command-failures", self.notifications.command_failures)
+        })?;
+
+        p.block("animations", |p| {
+            p.value("duration", self.animations.duration.as_millis())
+        })?;
+
+        p.block("tooltips", |p| {
+            p.value("timeout", self.tooltips.timeout.as_secs())?;
+            p.value("text-align", Variant(self.tooltips.text_align))?;
+            p.value("pin-on", Variant(self.tooltips.pin_on))
+        })?;
+
+        for (name, align) in [
+            ("start", BarAlignment::Start),
+            ("middle", BarAlignment::Middle),
+            ("end", BarAlignment::End),
+        ] {
+            let mut modules: Vec<_> = self
+                .modules
+                .iter()
+                .filter(|(_, position)| position.align == align)
+                .collect();
+            modules.sort_unstable_by_key(|(_, position)| position.idx);
+
+            p.block(name, |p| {
+                modules.into_iter().try_for_each(|(m, _)| p.module(m))
+            })?;
+        }
+
+        Ok(())
+    }
+}
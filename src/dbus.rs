@@ -0,0 +1,152 @@
+use iced::{
+    Subscription,
+    futures::{SinkExt as _, StreamExt as _, channel::mpsc::Sender},
+};
+use parking_lot::RwLock;
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tracing::{debug, error, info};
+use zbus::fdo;
+
+use crate::{Message, config::ColorVars, modules::ModuleMsg};
+
+pub const DBUS_NAME: &str = "io.github.ind_e.FrostBar";
+pub const DBUS_PATH: &str = "/io/github/ind_e/FrostBar";
+
+/// Index and name of the focused niri workspace, published by the niri
+/// service so it can be queried without going through the iced runtime
+pub static FOCUSED_WORKSPACE: RwLock<Option<(u8, Option<String>)>> =
+    RwLock::new(None);
+
+struct FrostBarInterface {
+    tx: mpsc::Sender<ColorVars>,
+}
+
+#[zbus::interface(name = "io.github.ind_e.FrostBar")]
+impl FrostBarInterface {
+    /// Parse `colors` as a colors.kdl document and apply it without writing
+    /// anything to disk. Nothing is applied if parsing fails.
+    async fn set_colors(&self, colors: String) -> fdo::Result<()> {
+        let colors = ColorVars::parse("colors.kdl", &colors)
+            .map_err(|e| fdo::Error::InvalidArgs(format!("{e:?}")))?;
+
+        self.tx
+            .send(colors)
+            .await
+            .map_err(|e| fdo::Error::Failed(e.to_string()))
+    }
+
+    /// Index and name of the focused niri workspace. The name is empty for
+    /// unnamed workspaces.
+    async fn focused_workspace(&self) -> fdo::Result<(u8, String)> {
+        FOCUSED_WORKSPACE
+            .read()
+            .clone()
+            .map(|(idx, name)| (idx, name.unwrap_or_default()))
+            .ok_or_else(|| {
+                fdo::Error::Failed(
+                    "no focused workspace, is the niri module enabled?"
+                        .to_string(),
+                )
+            })
+    }
+}
+
+#[zbus::proxy(
+    interface = "io.github.ind_e.FrostBar",
+    default_service = "io.github.ind_e.FrostBar",
+    default_path = "/io/github/ind_e/FrostBar"
+)]
+trait FrostBar {
+    fn focused_workspace(&self) -> zbus::Result<(u8, String)>;
+}
+
+/// Ask a running bar for the focused workspace. Used by the CLI, which runs
+/// outside of the iced runtime and so needs its own.
+pub fn query_workspace() -> zbus::Result<(u8, String)> {
+    tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()?
+        .block_on(async {
+            let connection = zbus::Connection::session().await?;
+            FrostBarProxy::new(&connection)
+                .await?
+                .focused_workspace()
+                .await
+        })
+}
+
+async fn serve(tx: mpsc::Sender<ColorVars>) -> zbus::Result<()> {
+    let _connection = zbus::connection::Builder::session()?
+        .name(DBUS_NAME)?
+        .serve_at(DBUS_PATH, FrostBarInterface { tx })?
+        .build()
+        .await?;
+
+    debug!("dbus: serving {DBUS_NAME} at {DBUS_PATH}");
+
+    // the interface is served for as long as the connection is alive
+    std::future::pending::<()>().await;
+    Ok(())
+}
+
+pub fn subscription() -> Subscription<Message> {
+    Subscription::run(|| {
+        let (tx, rx) = mpsc::channel(16);
+
+        tokio::spawn(async move {
+            if let Err(e) = serve(tx).await {
+                error!("dbus: {e}");
+            }
+        });
+
+        ReceiverStream::new(rx)
+    })
+    .map(Message::SetColors)
+}
+
+#[zbus::proxy(
+    interface = "org.freedesktop.login1.Manager",
+    default_service = "org.freedesktop.login1",
+    default_path = "/org/freedesktop/login1"
+)]
+trait Login1Manager {
+    #[zbus(signal)]
+    fn prepare_for_sleep(&self, start: bool) -> zbus::Result<()>;
+}
+
+async fn watch_sleep(output: &mut Sender<Message>) -> zbus::Result<()> {
+    let connection = zbus::Connection::system().await?;
+    let mut signals = Login1ManagerProxy::new(&connection)
+        .await?
+        .receive_prepare_for_sleep()
+        .await?;
+
+    while let Some(signal) = signals.next().await {
+        // logind sends the signal with `start` set before suspending, and
+        // again with it unset after waking up
+        if !signal.args()?.start {
+            info!("resumed from suspend, refreshing modules");
+            if output
+                .send(Message::Module(ModuleMsg::Resumed))
+                .await
+                .is_err()
+            {
+                break;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Notifies the bar when the system wakes up from suspend, so that polled
+/// modules don't show stale data until their next poll
+pub fn resume_subscription() -> Subscription<Message> {
+    Subscription::run(|| {
+        iced::stream::channel(1, |mut output: Sender<Message>| async move {
+            if let Err(e) = watch_sleep(&mut output).await {
+                error!("dbus: failed to watch for resume from suspend: {e}");
+            }
+        })
+    })
+}
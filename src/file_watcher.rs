@@ -4,6 +4,7 @@
 // niri is licensed under the GNU General Public License v3.0 (GPL-3.0).
 
 use std::{
+    fs,
     hash::Hash,
     io,
     path::{Path, PathBuf},
@@ -11,6 +12,8 @@ use std::{
 };
 
 use iced::{Subscription, futures::channel::mpsc::Sender};
+use tokio::signal::unix::{Signal, SignalKind, signal};
+use tracing::{error, info};
 
 use crate::Message;
 
@@ -100,6 +103,9 @@ impl<'a> FileWatcher<'a> {
     }
 }
 
+/// Polls the config files for changes. A SIGHUP forces a reload of the
+/// config, even if it hasn't changed. It goes through the same watcher, so a
+/// change that a signal already reloaded isn't reloaded again on the next poll.
 pub fn watch_config(path: ConfigPath) -> Subscription<Message> {
     Subscription::run_with(path, move |path| {
         let path = path.clone();
@@ -107,10 +113,22 @@ pub fn watch_config(path: ConfigPath) -> Subscription<Message> {
             100,
             |mut output: Sender<CheckResult>| async move {
                 let mut watcher = FileWatcher::new(&path);
+                let mut hangup = signal(SignalKind::hangup())
+                    .inspect_err(|e| error!("failed to listen for SIGHUP: {e}"))
+                    .ok();
                 loop {
-                    tokio::time::sleep(POLLING_INTERVAL).await;
-
-                    let event = watcher.check();
+                    let reload = tokio::select! {
+                        () = tokio::time::sleep(POLLING_INTERVAL) => false,
+                        () = recv_signal(hangup.as_mut()) => true,
+                    };
+
+                    let mut event = watcher.check();
+                    if reload {
+                        info!("received SIGHUP, reloading config");
+                        if event.config == CheckType::Unchanged {
+                            event.config = CheckType::Changed;
+                        }
+                    }
 
                     if (event.config != CheckType::Unchanged
                         || event.colors != CheckType::Unchanged)
@@ -125,8 +143,99 @@ pub fn watch_config(path: ConfigPath) -> Subscription<Message> {
     .map(Message::FileWatcherEvent)
 }
 
+/// Polls `path` like the config, sending its contents whenever it changes,
+/// starting with the current ones. `None` while the file doesn't exist or
+/// can't be read.
+pub fn watch_file(path: PathBuf) -> Subscription<(PathBuf, Option<String>)> {
+    Subscription::run_with(path, move |path| {
+        let path = path.clone();
+        iced::stream::channel(
+            10,
+            |mut output: Sender<(PathBuf, Option<String>)>| async move {
+                let mut state = None;
+                let mut first = true;
+                loop {
+                    let check = FileWatcher::check_file(&path, &mut state);
+                    if sends_watched(&check, first) {
+                        first = false;
+                        let contents = match check {
+                            CheckType::Changed => read_watched(&path),
+                            _ => None,
+                        };
+                        if output.try_send((path.clone(), contents)).is_err() {
+                            break;
+                        }
+                    }
+                    tokio::time::sleep(POLLING_INTERVAL).await;
+                }
+            },
+        )
+    })
+}
+
+/// Whether a check of a watched file is worth sending. A missing file is only
+/// sent once, it stays missing on every poll after that.
+fn sends_watched(check: &CheckType, first: bool) -> bool {
+    match check {
+        CheckType::Changed | CheckType::Disappeared => true,
+        CheckType::Missing | CheckType::Unchanged => first,
+    }
+}
+
+fn read_watched(path: &Path) -> Option<String> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Some(contents.trim_end_matches('\n').to_string()),
+        Err(e) => {
+            error!("failed to read watched file {}: {e}", path.display());
+            None
+        }
+    }
+}
+
+/// Waits for the next signal, or forever if there is no signal to wait for
+async fn recv_signal(signal: Option<&mut Signal>) {
+    match signal {
+        Some(signal) => {
+            if signal.recv().await.is_none() {
+                std::future::pending::<()>().await;
+            }
+        }
+        None => std::future::pending().await,
+    }
+}
+
 fn see_path(path: &Path) -> io::Result<(SystemTime, PathBuf)> {
     let canon = path.canonicalize()?;
     let mtime = canon.metadata()?.modified()?;
     Ok((mtime, canon))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_file_is_sent_once() {
+        let path = Path::new("/nonexistent/frostbar-watched");
+        let mut state = None;
+
+        let check = FileWatcher::check_file(path, &mut state);
+        assert_eq!(check, CheckType::Missing);
+        assert!(sends_watched(&check, true));
+
+        let check = FileWatcher::check_file(path, &mut state);
+        assert_eq!(check, CheckType::Missing);
+        assert!(!sends_watched(&check, false));
+    }
+
+    #[test]
+    fn watched_contents_lose_trailing_newlines() {
+        let path = std::env::temp_dir()
+            .join(format!("frostbar-watched-{}", std::process::id()));
+        fs::write(&path, "42%\n\n").unwrap();
+        let contents = read_watched(&path);
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(contents.as_deref(), Some("42%"));
+    }
+}
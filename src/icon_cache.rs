@@ -9,7 +9,11 @@ use iced::widget::{
     image::{self},
     svg,
 };
-use tracing::warn;
+use parking_lot::RwLock;
+use rustc_hash::FxHashMap;
+use tracing::{debug, warn};
+
+use crate::config;
 
 const ICON_SIZE: u16 = 48;
 const ICON_SCALE: u16 = 2;
@@ -144,6 +148,22 @@ fn icon_path_from_name(icon_path: &str) -> Option<PathBuf> {
 #[derive(Debug, Clone)]
 pub struct IconCache {
     inner: Arc<DashMap<String, Icon>>,
+    settings: Arc<RwLock<IconSettings>>,
+}
+
+#[derive(Debug, Default)]
+struct IconSettings {
+    config: config::Icons,
+    overrides: FxHashMap<String, String>,
+}
+
+fn icon_from_name_or_path(icon: &str) -> Option<Icon> {
+    let path = Path::new(icon);
+    if path.is_absolute() {
+        load_icon_from_path(path)
+    } else {
+        icon_path_from_name(icon).and_then(|path| load_icon_from_path(&path))
+    }
 }
 
 #[profiling::function]
@@ -168,16 +188,48 @@ impl IconCache {
     pub fn new() -> Self {
         Self {
             inner: Arc::new(DashMap::new()),
+            settings: Arc::new(RwLock::new(IconSettings::default())),
         }
     }
 
+    /// Apply icon overrides and the fallback icon. Cached icons are dropped
+    /// when these change so they get resolved again. Returns whether they
+    /// changed, icons resolved before that are stale then.
+    pub fn configure(&self, config: &config::Icons) -> bool {
+        let mut settings = self.settings.write();
+        if settings.config == *config {
+            return false;
+        }
+
+        debug!("icon settings changed, clearing icon cache");
+        settings.overrides = config
+            .overrides
+            .iter()
+            .map(|o| (o.app_id.clone(), o.icon.clone()))
+            .collect();
+        settings.config = config.clone();
+        self.inner.clear();
+        true
+    }
+
     pub fn get_icon(&self, app_id: &str) -> Option<Icon> {
         if let Some(icon) = self.inner.get(app_id) {
             return Some(icon.clone());
         }
 
-        let icon = find_icon_path(app_id)
-            .and_then(|path| load_icon_from_path(&path))?;
+        let settings = self.settings.read();
+        let icon = if let Some(icon) = settings.overrides.get(app_id) {
+            icon_from_name_or_path(icon)
+        } else {
+            find_icon_path(app_id).and_then(|path| load_icon_from_path(&path))
+        }
+        .or_else(|| {
+            settings
+                .config
+                .fallback
+                .as_deref()
+                .and_then(icon_from_name_or_path)
+        })?;
 
         self.inner.insert(app_id.to_string(), icon.clone());
         Some(icon)
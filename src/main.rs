@@ -1,20 +1,27 @@
+use std::{path::PathBuf, time::Instant};
+
 use clap::Parser;
 use iced::{
     Alignment, Background, Color, Event, Font, Length, Pixels, Rectangle,
     Subscription, Task, Theme,
     border::rounded,
     font::{Family, Weight},
+    mouse,
     padding::{left, top},
     theme,
     widget::{
-        self, Column, Container, Row, container, selector::Target, stack,
+        self, Column, Container, MouseArea, Row, container, selector::Target,
+        stack,
     },
     window::Id,
 };
-use iced_layershell::settings::{LayerShellSettings, StartMode};
-use itertools::Itertools;
+use iced_layershell::{
+    actions::ActionCallback,
+    settings::{LayerShellSettings, StartMode},
+};
+use rustc_hash::FxHashMap;
 use tokio::process::Command as TokioCommand;
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 #[cfg(feature = "console")]
 use tracing_subscriber::EnvFilter;
 use tracing_subscriber::Layer;
@@ -24,25 +31,35 @@ use tracing_subscriber::{
     reload,
     util::SubscriberInitExt,
 };
-use zbus::Connection;
 
 use crate::{
-    cli::{Cli, handle_subcommand},
-    config::{Anchor, ColorVars, Config, MediaControl, RawConfig, splat_gaps},
+    cli::{Cli, handle_subcommand, init_config, print_config},
+    config::{
+        Anchor, AvoidRegion, ColorVars, Config, Layout, MediaControl,
+        MiddleMode, RawConfig, WhenEmpty,
+    },
     file_watcher::{CheckResult, CheckType, ConfigPath, watch_config},
     icon_cache::IconCache,
     modules::{
         BarAlignment, CommandSpec, ModuleAction, ModuleMsg, Modules,
-        mpris::mpris_player::PlayerProxy,
+        mouse_binds, mpris,
     },
     utils::{
-        log::{LogManager, get_default_filter, notification},
-        window::{open_tooltip_window, open_window},
+        animation::Fade,
+        corner,
+        gesture::{Gesture, GestureState},
+        log::{
+            LogManager, get_default_filter, notification,
+            set_notifications_enabled,
+        },
+        spacing::collapse_margins,
+        window::{open_corner_windows, open_tooltip_window, open_window},
     },
 };
 
 mod cli;
 mod config;
+mod dbus;
 mod file_watcher;
 mod icon_cache;
 mod modules;
@@ -60,6 +77,9 @@ pub const FIRA_CODE: Font = Font {
 
 pub const BAR_NAMESPACE: &str = "FrostBar";
 
+/// Space between the start and end sections and the ends of the bar
+const SECTION_PADDING: f32 = 5.0;
+
 #[cfg(feature = "tracy-allocations")]
 #[global_allocator]
 static GLOBAL: tracy_client::ProfiledAllocator<std::alloc::System> =
@@ -73,16 +93,26 @@ pub fn main() -> Result<(), iced_layershell::Error> {
     #[cfg(feature = "tracy")]
     tracy_client::Client::start();
 
-    iced_layershell::daemon(
-        || {
-            let cli = Cli::parse();
+    let cli = Cli::parse();
 
-            let log_manager = LogManager::init();
+    let log_manager = LogManager::init();
 
-            if let Some(sub) = cli.subcommand {
-                handle_subcommand(sub, &log_manager);
-            }
+    // subcommands act as clients of a running bar, or as one-off tools, and
+    // exit without starting the daemon
+    if let Some(sub) = cli.subcommand {
+        handle_subcommand(sub, &log_manager);
+    }
 
+    if cli.init_config {
+        init_config(cli.config_dir);
+    }
+
+    if cli.print_config {
+        print_config(cli.config_dir);
+    }
+
+    iced_layershell::daemon(
+        move || {
             let stderr_layer = fmt::layer()
                 .compact()
                 .with_writer(std::io::stderr)
@@ -105,7 +135,7 @@ pub fn main() -> Result<(), iced_layershell::Error> {
             registry.init();
 
             let (config, color_vars, config_path) =
-                RawConfig::init(cli.config_dir);
+                RawConfig::init(cli.config_dir.clone());
 
             let logfile_path = log_manager.setup_logging(&handle);
 
@@ -119,6 +149,7 @@ pub fn main() -> Result<(), iced_layershell::Error> {
         Bar::view,
     )
     .subscription(Bar::subscription)
+    .scale_factor(Bar::scale_factor)
     .style(Bar::style)
     .title(Bar::title)
     .theme(Bar::theme)
@@ -137,15 +168,21 @@ pub fn main() -> Result<(), iced_layershell::Error> {
     .run()
 }
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum MouseEvent {
     Workspace(u64),
+    Label(widget::Id),
+    Group(widget::Id),
 }
 
 #[derive(Debug, Clone, PartialEq)]
 pub struct TooltipId {
     pub id: widget::Id,
     pub bounds: Option<Rectangle>,
+    pub interactive: bool,
+    /// Pinned tooltips stay open, ignoring the pointer leaving them, until
+    /// they are clicked away
+    pub pinned: bool,
 }
 
 #[derive(Debug, Clone, PartialEq)]
@@ -160,6 +197,7 @@ pub enum Message {
     IcedEvent(Event),
     MediaControl(MediaControl, String),
     FileWatcherEvent(CheckResult),
+    SetColors(ColorVars),
 
     Command(CommandSpec),
     NoOp,
@@ -167,16 +205,33 @@ pub enum Message {
     OpenTooltip(widget::Id),
     TooltipPositionMeasured(TooltipId),
     CloseTooltip(widget::Id),
+    DismissTooltip,
+    TooltipTimeout(Id),
+    TooltipFadedOut(Id),
+    /// Where the tooltip box is in the window of an interactive tooltip
+    TooltipBoxMeasured(Id, Rectangle),
+    /// A mouse button was pressed in the window, whether or not a widget
+    /// handled the press
+    MousePressed(Id, mouse::Button),
+    /// The scale of the output a window is on changed
+    Rescaled(Id, f32),
+    /// A window got a new size, which includes when it's first configured
+    Resized(Id),
 
     // OpenMenu(widget::Id),
     // ActivateMenu(String),
     // MenuPositionMeasured(MenuId),
     // CloseMenu(widget::Id),
     Module(ModuleMsg),
+    Gesture(Gesture),
 }
 
 pub struct Bar {
     id: Id,
+    /// Whether the window with `id` is open, see [`WhenEmpty`]
+    open: bool,
+    /// Whether the config has no modules at all
+    empty: bool,
     config: Config,
     color_vars: ColorVars,
     path: ConfigPath,
@@ -186,9 +241,23 @@ pub struct Bar {
 
     tooltip_window_id: Option<Id>,
     active_tooltip_id: Option<TooltipId>,
+    tooltip_fade: Fade,
+    /// A closed tooltip whose window stays open until it has faded out
+    closing_tooltip: Option<(Id, TooltipId, Fade)>,
+    /// The box around the content of interactive tooltips
+    tooltip_box_id: widget::Id,
+    /// Where the box was last measured, and in which window
+    tooltip_box_bounds: Option<(Id, Rectangle)>,
 
     menu_window_id: Option<Id>,
     active_menu_id: Option<MenuId>,
+
+    gesture_state: GestureState,
+    /// Until when each throttled command, with its arguments, isn't spawned
+    /// again. Entries are dropped once that has passed.
+    throttled: FxHashMap<(String, Option<Vec<String>>), Instant>,
+
+    corner_windows: Vec<(Id, iced_layershell::reexport::Anchor)>,
 }
 
 #[profiling::all_functions]
@@ -200,13 +269,31 @@ impl Bar {
     ) -> (Self, Task<Message>) {
         let icon_cache = IconCache::new();
 
+        let empty = config.modules.is_empty();
+        if empty {
+            warn!("no modules are configured");
+        }
+
         let mut modules = Modules::new();
         modules.update_from_config(&mut config, &icon_cache);
+        set_notifications_enabled(config.notifications.enabled);
+
+        let fonts_task = load_font_files(&config.style.font_files);
+        let open = shown(&config, empty);
+        let (id, open_task, corner_windows, corners_task) = if open {
+            let (id, open_task) = open_window(&config.layout, empty);
+            let (corner_windows, corners_task) =
+                open_corner_windows(&config.layout);
+            (id, open_task, corner_windows, corners_task)
+        } else {
+            (Id::unique(), Task::none(), Vec::new(), Task::none())
+        };
 
-        let (id, open_task) = open_window(&config.layout);
-
+        let tooltip_fade = Fade::new(config.animations.duration);
         let bar = Self {
             id,
+            open,
+            empty,
             modules,
             icon_cache,
             config,
@@ -214,11 +301,21 @@ impl Bar {
             path,
             tooltip_window_id: None,
             active_tooltip_id: None,
+            tooltip_fade,
+            closing_tooltip: None,
+            tooltip_box_id: widget::Id::unique(),
+            tooltip_box_bounds: None,
             menu_window_id: None,
             active_menu_id: None,
+            gesture_state: GestureState::default(),
+            throttled: FxHashMap::default(),
+            corner_windows,
         };
 
-        (bar, open_task)
+        (
+            bar,
+            Task::batch([fonts_task, open_task.chain(corners_task)]),
+        )
     }
 
     fn title(&self, id: Id) -> Option<String> {
@@ -235,9 +332,46 @@ impl Bar {
 
     pub fn subscription(&self) -> Subscription<Message> {
         let iced_event_sub = iced::event::listen().map(Message::IcedEvent);
+        let window_sub =
+            iced::event::listen_with(|event, _status, id| match event {
+                Event::Mouse(mouse::Event::ButtonPressed(button)) => {
+                    Some(Message::MousePressed(id, button))
+                }
+                Event::Window(iced::window::Event::Rescaled(factor)) => {
+                    Some(Message::Rescaled(id, factor))
+                }
+                Event::Window(iced::window::Event::Resized(_)) => {
+                    Some(Message::Resized(id))
+                }
+                _ => None,
+            });
         let watch_config_sub = watch_config(self.path.clone());
         let modules_sub = self.modules.subscriptions();
-        Subscription::batch([iced_event_sub, watch_config_sub, modules_sub])
+        let dbus_sub = dbus::subscription();
+        let resume_sub = dbus::resume_subscription();
+
+        let now = Instant::now();
+        let fading = self.tooltip_fade.is_animating(now)
+            || self
+                .closing_tooltip
+                .as_ref()
+                .is_some_and(|(_, _, fade)| fade.is_animating(now));
+        let fade_sub = if fading {
+            iced::window::frames()
+                .map(|_| Message::Module(ModuleMsg::AnimationFrame))
+        } else {
+            Subscription::none()
+        };
+
+        Subscription::batch([
+            iced_event_sub,
+            window_sub,
+            watch_config_sub,
+            modules_sub,
+            dbus_sub,
+            resume_sub,
+            fade_sub,
+        ])
     }
 
     pub fn update(&mut self, message: Message) -> Task<Message> {
@@ -247,6 +381,26 @@ impl Bar {
         });
         #[cfg(feature = "tracy")]
         let _span = tracy_client::span!("iced_update");
+
+        // the content of an interactive tooltip can change size without its
+        // window being resized, e.g. when the track changes, so it's measured
+        // again after anything that can rebuild it
+        let remeasure = matches!(
+            message,
+            Message::Module(_)
+                | Message::MediaControl(..)
+                | Message::FileWatcherEvent(_)
+                | Message::SetColors(_)
+        );
+        let task = self.handle(message);
+        if remeasure && self.tooltip_window_id.is_some() {
+            Task::batch([task, self.measure_tooltip_box()])
+        } else {
+            task
+        }
+    }
+
+    fn handle(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::IcedEvent(event) => {
                 // if let Event::Window(iced::window::Event::Opened {
@@ -324,6 +478,7 @@ impl Bar {
             //     }
             // }
             Message::OpenTooltip(id) => {
+                let interactive = self.modules.tooltip_is_interactive(&id);
                 return widget::selector::find(id.clone()).map(move |target| {
                     if let Some(Target::Container { visible_bounds, .. }) =
                         target
@@ -331,6 +486,8 @@ impl Bar {
                         Message::TooltipPositionMeasured(TooltipId {
                             id: id.clone(),
                             bounds: visible_bounds,
+                            interactive,
+                            pinned: false,
                         })
                     } else {
                         error!("failed to find tooltip");
@@ -339,33 +496,160 @@ impl Bar {
                 });
             }
             Message::TooltipPositionMeasured(tooltip_id) => {
+                // a pinned tooltip stays until it's clicked away, hovering
+                // other modules doesn't replace it
+                if self.active_tooltip_id.as_ref().is_some_and(|t| t.pinned) {
+                    return Task::none();
+                }
+
                 let old_id = self.tooltip_window_id.take();
 
-                let (win_id, open_task) = open_tooltip_window();
+                let (win_id, open_task) =
+                    open_tooltip_window(tooltip_id.interactive);
                 self.tooltip_window_id = Some(win_id);
                 self.active_tooltip_id = Some(tooltip_id);
 
+                // moving between modules swaps the tooltip without fading
+                if old_id.is_none() {
+                    self.tooltip_fade =
+                        Fade::new(self.config.animations.duration);
+                    self.tooltip_fade.set_shown(true);
+                }
+
+                // a safety net for tooltips whose exit event got lost, e.g.
+                // when the pointer leaves the bar across a screen edge
+                let timeout = self.config.tooltips.timeout;
+                let timeout_task = if timeout.is_zero() {
+                    Task::none()
+                } else {
+                    Task::perform(tokio::time::sleep(timeout), move |()| {
+                        Message::TooltipTimeout(win_id)
+                    })
+                };
+
                 if let Some(old_id) = old_id {
                     debug!(
                         "opening tooltip {}, closing tooltip {}",
                         self.tooltip_window_id.unwrap(),
                         old_id
                     );
-                    return open_task.chain(iced::window::close(old_id));
+                    return Task::batch([
+                        open_task.chain(iced::window::close(old_id)),
+                        timeout_task,
+                    ]);
                 }
 
                 debug!("opening tooltip {}", self.tooltip_window_id.unwrap());
-                return open_task;
+                return Task::batch([open_task, timeout_task]);
             }
             Message::CloseTooltip(id) => {
-                if self.active_tooltip_id.as_ref().is_some_and(|t| t.id == id)
-                    && let Some(window_id) = self.tooltip_window_id.take()
+                // interactive tooltips are closed with DismissTooltip once
+                // the pointer leaves the popup itself
+                if self
+                    .active_tooltip_id
+                    .as_ref()
+                    .is_some_and(|t| t.id == id && !t.interactive)
+                {
+                    return self.close_tooltip();
+                }
+            }
+            Message::DismissTooltip => {
+                return self.close_tooltip();
+            }
+            Message::TooltipTimeout(window_id) => {
+                // the timer of a tooltip that was already replaced or closed
+                // must not close its successor
+                if self.tooltip_window_id == Some(window_id)
+                    && self
+                        .active_tooltip_id
+                        .as_ref()
+                        .is_some_and(|t| !t.interactive)
+                {
+                    debug!("tooltip {} timed out", window_id);
+                    return self.close_tooltip();
+                }
+            }
+            Message::MousePressed(window_id, button) => {
+                if window_id == self.id {
+                    // clicks outside of a pinned tooltip go to whatever is
+                    // below it, so it's clicked away on the bar instead
+                    if self.active_tooltip_id.as_ref().is_some_and(|t| t.pinned)
+                    {
+                        return self.close_tooltip();
+                    }
+                    if Some(button) == self.config.tooltips.pin_on.button() {
+                        return self.pin_tooltip();
+                    }
+                }
+            }
+            Message::Resized(window_id) => {
+                if self.tooltip_window_id == Some(window_id) {
+                    return self.measure_tooltip_box();
+                }
+            }
+            Message::TooltipBoxMeasured(window_id, bounds) => {
+                if self.tooltip_box_bounds == Some((window_id, bounds)) {
+                    return Task::none();
+                }
+                self.tooltip_box_bounds = Some((window_id, bounds));
+
+                // the window covers the whole screen, but only the tooltip
+                // box takes input, so clicks next to it aren't swallowed
+                let bounds = bounds * self.config.layout.factor();
+                let (x, y) = (bounds.x.floor() as i32, bounds.y.floor() as i32);
+                let (width, height) =
+                    (bounds.width.ceil() as i32, bounds.height.ceil() as i32);
+                return Task::done(Message::SetInputRegion {
+                    id: window_id,
+                    callback: ActionCallback::new(move |region| {
+                        region.add(x, y, width, height);
+                    }),
+                });
+            }
+            Message::Rescaled(window_id, factor) => {
+                if window_id == self.id
+                    && (factor - self.config.layout.scale.output).abs()
+                        > f32::EPSILON
+                {
+                    debug!("output scale changed to {factor}");
+                    let mut new_layout = self.config.layout.clone();
+                    new_layout.scale.output = factor;
+                    return self.change_layout(new_layout);
+                }
+            }
+            Message::TooltipFadedOut(window_id) => {
+                if self
+                    .closing_tooltip
+                    .as_ref()
+                    .is_some_and(|(id, ..)| *id == window_id)
                 {
-                    debug!("closing tooltip {}", window_id);
-                    self.active_tooltip_id = None;
+                    self.closing_tooltip = None;
                     return iced::window::close(window_id);
                 }
             }
+            Message::Gesture(Gesture::Click(message)) => {
+                if self
+                    .gesture_state
+                    .click(&self.config.gestures, Instant::now())
+                {
+                    return self.update(*message);
+                }
+                debug!("ignoring click during scroll gesture");
+            }
+            Message::Gesture(Gesture::Scroll { x, y, binds }) => {
+                if let Some(message) = self
+                    .gesture_state
+                    .scroll(&self.config.gestures, Instant::now(), x, y)
+                    .and_then(|direction| direction.bind(*binds))
+                {
+                    return self.update(message);
+                }
+            }
+            Message::SetColors(color_vars) => {
+                debug!("applying colors from dbus");
+                self.color_vars = color_vars;
+                return self.reload_config();
+            }
             Message::FileWatcherEvent(event) => {
                 match event.colors {
                     CheckType::Changed => {
@@ -411,47 +695,12 @@ impl Bar {
                     ModuleAction::None => {}
                 }
             }
-            Message::MediaControl(control, player) => {
+            Message::MediaControl(control, player_name) => {
                 return Task::perform(
                     async move {
-                        if let Ok(connection) = Connection::session().await
-                            && let Ok(player) =
-                                PlayerProxy::new(&connection, player).await
-                            && let Err(e) = match control {
-                                MediaControl::Play => player.play().await,
-                                MediaControl::Pause => player.pause().await,
-                                MediaControl::PlayPause => {
-                                    player.play_pause().await
-                                }
-                                MediaControl::Stop => player.stop().await,
-                                MediaControl::Next => player.next().await,
-                                MediaControl::Previous => {
-                                    player.previous().await
-                                }
-                                MediaControl::Seek(amount) => {
-                                    player.seek(amount).await
-                                }
-                                MediaControl::Volume(amount) => {
-                                    match player.volume().await {
-                                        Ok(current) => {
-                                            player
-                                                .set_volume(
-                                                    (current
-                                                        + f64::from(amount))
-                                                    .max(0.0),
-                                                )
-                                                .await
-                                        }
-                                        Err(e) => Err(e),
-                                    }
-                                }
-
-                                MediaControl::SetVolume(amount) => {
-                                    player
-                                        .set_volume(f64::from(amount.max(0.0)))
-                                        .await
-                                }
-                            }
+                        if let Err(e) =
+                            mpris::service::media_control(control, &player_name)
+                                .await
                         {
                             error!("{e}");
                         }
@@ -460,6 +709,19 @@ impl Bar {
                 );
             }
             Message::Command(cmd) => {
+                // binds with different throttles, or none, still share the
+                // command's last spawn
+                let now = Instant::now();
+                self.throttled.retain(|_, until| *until > now);
+                if let Some(throttle) = cmd.throttle {
+                    let key = (cmd.command.clone(), cmd.args.clone());
+                    if self.throttled.contains_key(&key) {
+                        debug!("throttled `{cmd}`");
+                        return Task::none();
+                    }
+                    self.throttled.insert(key, now + throttle);
+                }
+                let notify = self.config.notifications.command_failures;
                 return Task::future(async move {
                     let mut command = TokioCommand::new(&cmd.command);
                     if let Some(ref args) = cmd.args {
@@ -487,6 +749,11 @@ impl Bar {
 
                         Err(e) => {
                             error!("failed to spawn `{cmd}`: {e}");
+                            if notify {
+                                notification(&format!(
+                                    "Failed to run `{cmd}`: {e}"
+                                ));
+                            }
                         }
                     }
 
@@ -516,108 +783,199 @@ impl Bar {
         Task::none()
     }
 
+    /// Turns the open tooltip into an interactive popup that stays open until
+    /// it's clicked away
+    /// Finds where the box of an interactive tooltip is, so that its input
+    /// region can follow it
+    fn measure_tooltip_box(&self) -> Task<Message> {
+        let Some(window_id) = self.tooltip_window_id else {
+            return Task::none();
+        };
+        if !self
+            .active_tooltip_id
+            .as_ref()
+            .is_some_and(|t| t.interactive)
+        {
+            return Task::none();
+        }
+
+        widget::selector::find(self.tooltip_box_id.clone()).map(move |target| {
+            if let Some(Target::Container {
+                visible_bounds: Some(bounds),
+                ..
+            }) = target
+            {
+                Message::TooltipBoxMeasured(window_id, bounds)
+            } else {
+                Message::NoOp
+            }
+        })
+    }
+
+    fn pin_tooltip(&mut self) -> Task<Message> {
+        let Some(tooltip_id) = self.active_tooltip_id.as_mut() else {
+            return Task::none();
+        };
+        if tooltip_id.pinned {
+            return Task::none();
+        }
+        tooltip_id.pinned = true;
+        debug!("pinning tooltip {:?}", tooltip_id.id);
+        if tooltip_id.interactive {
+            return Task::none();
+        }
+        tooltip_id.interactive = true;
+
+        // whether a window takes input is fixed when it's opened, so the
+        // tooltip is swapped for an interactive one, without fading
+        let (win_id, open_task) = open_tooltip_window(true);
+        let old_id = self.tooltip_window_id.replace(win_id);
+        match old_id {
+            Some(old_id) => open_task.chain(iced::window::close(old_id)),
+            None => open_task,
+        }
+    }
+
+    /// Closes the open tooltip, fading it out first if animations are enabled
+    fn close_tooltip(&mut self) -> Task<Message> {
+        let (Some(window_id), Some(tooltip_id)) =
+            (self.tooltip_window_id.take(), self.active_tooltip_id.take())
+        else {
+            return Task::none();
+        };
+        debug!("closing tooltip {}", window_id);
+
+        // only one tooltip fades out at a time
+        let previous = self
+            .closing_tooltip
+            .take()
+            .map_or_else(Task::none, |(id, ..)| iced::window::close(id));
+
+        let duration = self.config.animations.duration;
+        if duration.is_zero() {
+            return Task::batch([previous, iced::window::close(window_id)]);
+        }
+
+        // fade out from wherever the fade in got to
+        let mut fade =
+            std::mem::replace(&mut self.tooltip_fade, Fade::new(duration));
+        fade.set_shown(false);
+        // a fading tooltip no longer reacts to the pointer, so it can't
+        // dismiss the tooltip that replaces it
+        let tooltip_id = TooltipId {
+            interactive: false,
+            pinned: false,
+            ..tooltip_id
+        };
+        self.closing_tooltip = Some((window_id, tooltip_id, fade));
+
+        Task::batch([
+            previous,
+            Task::perform(tokio::time::sleep(duration), move |()| {
+                Message::TooltipFadedOut(window_id)
+            }),
+        ])
+    }
+
     #[inline(always)]
     fn view_bar(&self) -> Element<'_> {
-        let mut start_views: Vec<(Element, usize)> = vec![];
-        let mut middle_views: Vec<(Element, usize)> = vec![];
-        let mut end_views: Vec<(Element, usize)> = vec![];
+        let mut start_views = vec![];
+        let mut middle_views = vec![];
+        let mut end_views = vec![];
 
-        for (element, position) in
+        for (element, position, margin) in
             self.modules.render_views(&self.config.layout)
         {
+            let view = (element, position.idx, margin);
             match position.align {
-                BarAlignment::Start => {
-                    start_views.push((element, position.idx));
-                }
-                BarAlignment::Middle => {
-                    middle_views.push((element, position.idx));
-                }
-                BarAlignment::End => end_views.push((element, position.idx)),
+                BarAlignment::Start => start_views.push(view),
+                BarAlignment::Middle => middle_views.push(view),
+                BarAlignment::End => end_views.push(view),
             }
         }
 
-        let start_views: Vec<Element> = start_views
-            .into_iter()
-            .sorted_unstable_by_key(|(_, idx)| *idx)
-            .map(|(v, _)| v)
-            .collect();
-
-        let middle_views: Vec<Element> = middle_views
-            .into_iter()
-            .sorted_unstable_by_key(|(_, idx)| *idx)
-            .map(|(v, _)| v)
-            .collect();
-
-        let end_views: Vec<Element> = end_views
-            .into_iter()
-            .sorted_unstable_by_key(|(_, idx)| *idx)
-            .map(|(v, _)| v)
-            .collect();
-
         let vertical = self.config.layout.anchor.vertical();
+        let spacing = self.config.layout.section_spacing();
+
+        let start_section = Container::new(section(
+            start_views,
+            vertical,
+            spacing,
+            SECTION_PADDING,
+        ));
 
         let start_section = if vertical {
-            Container::new(
-                Column::with_children(start_views)
-                    .align_x(Alignment::Center)
-                    .padding(top(5).bottom(5)),
-            )
-            .align_x(Alignment::Center)
-            .align_y(Alignment::Start)
+            start_section
+                .align_x(Alignment::Center)
+                .align_y(Alignment::Start)
         } else {
-            Container::new(
-                Row::with_children(start_views)
-                    .align_y(Alignment::Center)
-                    .padding(left(5).right(5))
-                    .spacing(5),
-            )
-            .align_x(Alignment::Start)
-            .align_y(Alignment::Center)
+            start_section
+                .align_x(Alignment::Start)
+                .align_y(Alignment::Center)
         };
 
         let start_section =
             start_section.width(Length::Fill).height(Length::Fill);
 
-        let middle_section = if vertical {
-            Container::new(
-                Column::with_children(middle_views).align_x(Alignment::Center),
-            )
-        } else {
-            Container::new(
-                Row::with_children(middle_views)
+        let avoid = self.config.layout.avoid_region.filter(|_| !vertical);
+        let distributed = avoid.is_none()
+            && self.config.layout.middle_mode == MiddleMode::Distribute;
+
+        let middle_section: Element = match avoid {
+            Some(region) => avoid_region(
+                middle_views,
+                region,
+                self.config.layout.margin().3,
+                self.config.layout.factor(),
+                spacing,
+            ),
+            None if distributed => distribute(middle_views, vertical),
+            None => {
+                Container::new(section(middle_views, vertical, spacing, 0.0))
+                    .width(Length::Fill)
+                    .height(Length::Fill)
+                    .align_x(Alignment::Center)
                     .align_y(Alignment::Center)
-                    .spacing(5),
-            )
+                    .into()
+            }
         };
 
-        let middle_section = middle_section
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .align_x(Alignment::Center)
-            .align_y(Alignment::Center);
+        let end_section = Container::new(section(
+            end_views,
+            vertical,
+            spacing,
+            SECTION_PADDING,
+        ));
 
         let end_section = if vertical {
-            Container::new(
-                Column::with_children(end_views)
-                    .align_x(Alignment::Center)
-                    .padding(top(5).bottom(5)),
-            )
-            .align_x(Alignment::Center)
-            .align_y(Alignment::End)
+            end_section
+                .align_x(Alignment::Center)
+                .align_y(Alignment::End)
         } else {
-            Container::new(
-                Row::with_children(end_views)
-                    .align_y(Alignment::Center)
-                    .spacing(5)
-                    .padding(left(5).right(5)),
-            )
-            .align_x(Alignment::End)
-            .align_y(Alignment::Center)
+            end_section
+                .align_x(Alignment::End)
+                .align_y(Alignment::Center)
         };
 
         let end_section = end_section.width(Length::Fill).height(Length::Fill);
 
-        let layout = stack![start_section, middle_section, end_section];
+        // distributed modules share the space between the start and end
+        // sections, so that they never overlap them
+        let layout: Element = if distributed && vertical {
+            Column::new()
+                .push(start_section.height(Length::Shrink))
+                .push(middle_section)
+                .push(end_section.height(Length::Shrink))
+                .into()
+        } else if distributed {
+            Row::new()
+                .push(start_section.width(Length::Shrink))
+                .push(middle_section)
+                .push(end_section.width(Length::Shrink))
+                .into()
+        } else {
+            stack![start_section, middle_section, end_section].into()
+        };
 
         let bar = if vertical {
             Container::new(layout)
@@ -629,32 +987,47 @@ impl Bar {
                 .height(Length::Fixed(self.config.layout.width as f32))
         };
 
-        bar.style(|_theme| container::Style {
+        let bar = bar.style(|_theme| container::Style {
             background: Some(Background::Color(self.config.style.background)),
             border: rounded(self.config.style.border_radius),
             ..Default::default()
-        })
-        .into()
+        });
+
+        // modules capture the buttons they have binds for, so these only
+        // fire for the rest of the bar
+        mouse_binds(bar, &self.config.bar_binds, None)
     }
 
     #[inline(always)]
-    fn view_tooltip<'a>(&'a self, tooltip_id: &'a TooltipId) -> Element<'a> {
+    fn view_tooltip<'a>(
+        &'a self,
+        tooltip_id: &'a TooltipId,
+        fade: &Fade,
+    ) -> Element<'a> {
         let content = self
             .modules
             .render_tooltip_for_id(&tooltip_id.id)
             .unwrap_or_else(|| Column::new().into());
 
+        // fading only changes the look; interactive tooltips take input as
+        // soon as they open
+        let opacity = fade.progress(Instant::now());
+
         let bounds = tooltip_id.bounds.unwrap_or_default();
         let mut container =
-            Container::new(content).padding(5).style(|_theme: &Theme| {
-                container::Style {
+            Container::new(content)
+                .padding(5)
+                .style(move |theme: &Theme| container::Style {
                     background: Some(Background::Color(
-                        self.config.style.background,
+                        self.config.style.background.scale_alpha(opacity),
                     )),
+                    text_color: Some(theme.palette().text.scale_alpha(opacity)),
                     border: rounded(self.config.style.border_radius),
                     ..Default::default()
-                }
-            });
+                });
+        if tooltip_id.interactive {
+            container = container.id(self.tooltip_box_id.clone());
+        }
         match self.config.layout.anchor {
             Anchor::Right => {
                 container = Container::new(container).align_right(Length::Fill);
@@ -666,16 +1039,33 @@ impl Bar {
             Anchor::Top | Anchor::Left => {}
         }
 
+        if tooltip_id.pinned {
+            // clicks on the tooltip itself must not dismiss it
+            container = Container::new(
+                MouseArea::new(container).on_press(Message::NoOp),
+            );
+        } else if tooltip_id.interactive {
+            container = Container::new(
+                MouseArea::new(container).on_exit(Message::DismissTooltip),
+            );
+        }
+
         let pin = if self.config.layout.anchor.vertical() {
             iced::widget::pin(container).y(bounds.y)
         } else {
             iced::widget::pin(container).x(bounds.x)
         };
 
-        Container::new(pin)
-            .width(Length::Fill)
-            .height(Length::Fill)
-            .into()
+        let content =
+            Container::new(pin).width(Length::Fill).height(Length::Fill);
+
+        if tooltip_id.interactive {
+            MouseArea::new(content)
+                .on_press(Message::DismissTooltip)
+                .into()
+        } else {
+            content.into()
+        }
     }
 
     // #[inline(always)]
@@ -730,7 +1120,21 @@ impl Bar {
         } else if Some(id) == self.tooltip_window_id
             && let Some(tooltip_id) = &self.active_tooltip_id
         {
-            self.view_tooltip(tooltip_id)
+            self.view_tooltip(tooltip_id, &self.tooltip_fade)
+        } else if let Some((_, tooltip_id, fade)) = self
+            .closing_tooltip
+            .as_ref()
+            .filter(|(window, ..)| *window == id)
+        {
+            self.view_tooltip(tooltip_id, fade)
+        } else if let Some((_, anchor)) =
+            self.corner_windows.iter().find(|(window, _)| *window == id)
+        {
+            corner::view(
+                self.config.style.background,
+                self.config.layout.screen_corner_radius.into(),
+                *anchor,
+            )
         }
         // else if Some(id) == self.menu_window_id
         //     && let Some(menu_id) = &self.active_menu_id
@@ -754,21 +1158,41 @@ impl Bar {
         Theme::Dark
     }
 
+    /// Scales the bar, its tooltips and its corners alike
+    pub fn scale_factor(&self, _id: Id) -> f32 {
+        self.config.layout.factor()
+    }
+
     fn reload_config(&mut self) -> Task<Message> {
         match RawConfig::load(&self.path.config) {
             Ok(new_config) => {
                 let mut new_config = new_config.hydrate(&self.color_vars);
+                // the output scale isn't part of the config file
+                new_config.layout.scale.output =
+                    self.config.layout.scale.output;
+                let empty = new_config.modules.is_empty();
                 self.modules
                     .update_from_config(&mut new_config, &self.icon_cache);
+                set_notifications_enabled(new_config.notifications.enabled);
 
-                if self.config.layout == new_config.layout {
-                    self.config = new_config;
-                    self.modules.synchronize_views();
+                let fonts_task = if self.config.style.font_files
+                    == new_config.style.font_files
+                {
+                    Task::none()
                 } else {
-                    let task = self.update_layout(new_config);
+                    load_font_files(&new_config.style.font_files)
+                };
+
+                if self.config.layout == new_config.layout
+                    && self.empty == empty
+                {
+                    self.config = new_config;
                     self.modules.synchronize_views();
-                    return task;
+                    return fonts_task;
                 }
+                let task = self.update_layout(new_config, empty);
+                self.modules.synchronize_views();
+                return Task::batch([fonts_task, task]);
             }
             Err(e) => {
                 error!("{:?}", e);
@@ -780,12 +1204,93 @@ impl Bar {
         Task::none()
     }
 
-    fn update_layout(&mut self, new_config: Config) -> Task<Message> {
-        let old_layout = &self.config.layout;
-        let new_layout = &new_config.layout;
+    fn update_layout(
+        &mut self,
+        new_config: Config,
+        empty: bool,
+    ) -> Task<Message> {
+        let old_empty = std::mem::replace(&mut self.empty, empty);
+        if empty && !old_empty {
+            warn!("no modules are configured");
+        }
 
         let mut tasks = Vec::new();
 
+        match (self.open, shown(&new_config, empty)) {
+            (true, true) => {}
+            (true, false) => {
+                debug!("closing the bar, it has no modules");
+                tasks.push(iced::window::close(self.id));
+                tasks.extend(
+                    self.corner_windows
+                        .drain(..)
+                        .map(|(id, _)| iced::window::close(id)),
+                );
+                self.open = false;
+                self.config = new_config;
+                return Task::batch(tasks);
+            }
+            (false, true) => {
+                let (id, open_task) = open_window(&new_config.layout, empty);
+                let (corner_windows, corners_task) =
+                    open_corner_windows(&new_config.layout);
+                self.id = id;
+                self.corner_windows = corner_windows;
+                self.open = true;
+                self.config = new_config;
+                return open_task.chain(corners_task);
+            }
+            (false, false) => {
+                self.config = new_config;
+                return Task::none();
+            }
+        }
+
+        tasks.push(self.layout_tasks(&new_config.layout, old_empty));
+        self.config = new_config;
+        Task::batch(tasks)
+    }
+
+    /// Applies a changed layout to the open bar, e.g. after the output's
+    /// scale changed
+    fn change_layout(&mut self, new_layout: Layout) -> Task<Message> {
+        if !self.open {
+            self.config.layout = new_layout;
+            return Task::none();
+        }
+        let task = self.layout_tasks(&new_layout, self.empty);
+        self.config.layout = new_layout;
+        task
+    }
+
+    /// Tasks that move the open bar from the current layout to `new_layout`
+    fn layout_tasks(
+        &mut self,
+        new_layout: &Layout,
+        old_empty: bool,
+    ) -> Task<Message> {
+        let mut tasks = Vec::new();
+        let old_layout = &self.config.layout;
+        let empty = self.empty;
+
+        // corner windows are cheap, so they are reopened rather than moved
+        if old_layout.screen_corner_radius != new_layout.screen_corner_radius
+            || old_layout.anchor != new_layout.anchor
+            || old_layout.width != new_layout.width
+            || old_layout.scale != new_layout.scale
+            || old_layout.margin() != new_layout.margin()
+            || old_layout.layer != new_layout.layer
+        {
+            tasks.extend(
+                self.corner_windows
+                    .drain(..)
+                    .map(|(id, _)| iced::window::close(id)),
+            );
+            let (corner_windows, open_task) = open_corner_windows(new_layout);
+            self.corner_windows = corner_windows;
+            tasks.push(open_task);
+        }
+
         if old_layout.layer != new_layout.layer {
             tasks.push(Task::done(Message::LayerChange {
                 id: self.id,
@@ -800,20 +1305,215 @@ impl Bar {
             }));
         }
 
-        if old_layout.width != new_layout.width {
+        if old_layout.surface_size() != new_layout.surface_size() {
             tasks.push(Task::done(Message::SizeChange {
                 id: self.id,
-                size: new_layout.anchor.calc_size(new_layout.width),
+                size: new_layout.surface_size(),
             }));
         }
 
-        if old_layout.gaps != new_layout.gaps {
+        if old_layout.margin() != new_layout.margin() {
             tasks.push(Task::done(Message::MarginChange {
                 id: self.id,
-                margin: splat_gaps(new_layout.gaps),
+                margin: new_layout.margin(),
+            }));
+        }
+
+        if old_layout.keyboard_interactivity
+            != new_layout.keyboard_interactivity
+        {
+            tasks.push(Task::done(Message::KeyboardInteractivityChange {
+                id: self.id,
+                keyboard_interactivity: new_layout
+                    .keyboard_interactivity
+                    .into(),
+            }));
+        }
+
+        let old_zone = if old_empty {
+            0
+        } else {
+            old_layout.exclusive_zone()
+        };
+        let new_zone = if empty {
+            0
+        } else {
+            new_layout.exclusive_zone()
+        };
+        if old_zone != new_zone {
+            tasks.push(Task::done(Message::ExclusiveZoneChange {
+                id: self.id,
+                zone_size: new_zone,
             }));
         }
-        self.config = new_config;
         Task::batch(tasks)
     }
 }
+
+/// Whether the bar's window should be open
+fn shown(config: &Config, empty: bool) -> bool {
+    !empty || config.layout.when_empty == WhenEmpty::Show
+}
+
+/// Lay out the views of one section in bar order, separated by their
+/// collapsed margins
+fn section<'a>(
+    mut views: Vec<(Element<'a>, usize, f32)>,
+    vertical: bool,
+    spacing: f32,
+    edge: f32,
+) -> Element<'a> {
+    views.sort_unstable_by_key(|(_, idx, _)| *idx);
+
+    let margins: Vec<f32> =
+        views.iter().map(|(_, _, margin)| *margin).collect();
+    let gaps = collapse_margins(&margins, spacing, edge);
+    let last = views.len().saturating_sub(1);
+
+    let views = views.into_iter().enumerate().map(|(i, (view, _, _))| {
+        let after = if i == last { gaps[i + 1] } else { 0.0 };
+        let padding = if vertical {
+            top(gaps[i]).bottom(after)
+        } else {
+            left(gaps[i]).right(after)
+        };
+        Container::new(view).padding(padding).into()
+    });
+
+    if vertical {
+        Column::with_children(views)
+            .align_x(Alignment::Center)
+            .into()
+    } else {
+        Row::with_children(views).align_y(Alignment::Center).into()
+    }
+}
+
+/// Give each view an equal share of the bar and center it within that share
+fn distribute<'a>(
+    mut views: Vec<(Element<'a>, usize, f32)>,
+    vertical: bool,
+) -> Element<'a> {
+    views.sort_unstable_by_key(|(_, idx, _)| *idx);
+
+    let views = views.into_iter().map(|(view, _, _)| {
+        Container::new(view)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .align_x(Alignment::Center)
+            .align_y(Alignment::Center)
+            .into()
+    });
+
+    if vertical {
+        Column::with_children(views).height(Length::Fill).into()
+    } else {
+        Row::with_children(views).width(Length::Fill).into()
+    }
+}
+
+/// Split the middle section of a horizontal bar around `region`. The first
+/// half of the views ends at the left edge of the region and the rest start
+/// at its right edge.
+fn avoid_region<'a>(
+    mut views: Vec<(Element<'a>, usize, f32)>,
+    region: AvoidRegion,
+    left_gap: i32,
+    scale: f32,
+    spacing: f32,
+) -> Element<'a> {
+    views.sort_unstable_by_key(|(_, idx, _)| *idx);
+    let after = views.split_off(views.len().div_ceil(2));
+
+    // the region is relative to the output, but the bar starts after the gap.
+    // Both are in unscaled pixels, while the bar is laid out scaled.
+    let start = ((f32::from(region.x) - left_gap as f32) / scale).max(0.0);
+    let width = f32::from(region.width) / scale;
+
+    Row::new()
+        .push(
+            Container::new(section(views, false, spacing, 0.0))
+                .width(Length::Fixed(start))
+                .height(Length::Fill)
+                .align_x(Alignment::End)
+                .align_y(Alignment::Center),
+        )
+        .push(Row::new().width(Length::Fixed(width)))
+        .push(
+            Container::new(section(after, false, spacing, 0.0))
+                .width(Length::Fill)
+                .height(Length::Fill)
+                .align_x(Alignment::Start)
+                .align_y(Alignment::Center),
+        )
+        .into()
+}
+
+/// Loads extra fonts, e.g. an icon font that isn't installed system-wide
+fn load_font_files(files: &[PathBuf]) -> Task<Message> {
+    Task::batch(files.iter().filter_map(|file| match std::fs::read(file) {
+        Ok(bytes) => {
+            let file = file.clone();
+            Some(iced::font::load(bytes).map(move |result| {
+                if let Err(e) = result {
+                    error!("failed to load font {}: {e:?}", file.display());
+                }
+                Message::NoOp
+            }))
+        }
+        Err(e) => {
+            error!("failed to read font {}: {e}", file.display());
+            None
+        }
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bar() -> Bar {
+        let colors = ColorVars::default();
+        let config = RawConfig::parse("", "").unwrap().hydrate(&colors);
+        let path = ConfigPath {
+            config: PathBuf::new(),
+            colors: PathBuf::new(),
+        };
+        Bar::new(config, colors, path).0
+    }
+
+    fn tooltip_id(pinned: bool) -> TooltipId {
+        TooltipId {
+            id: widget::Id::unique(),
+            bounds: None,
+            interactive: true,
+            pinned,
+        }
+    }
+
+    #[test]
+    fn hovering_keeps_pinned_tooltip() {
+        let mut bar = bar();
+        let pinned = tooltip_id(true);
+        let window = Id::unique();
+        bar.tooltip_window_id = Some(window);
+        bar.active_tooltip_id = Some(pinned.clone());
+
+        let _ = bar.update(Message::TooltipPositionMeasured(tooltip_id(false)));
+
+        assert_eq!(bar.active_tooltip_id, Some(pinned));
+        assert_eq!(bar.tooltip_window_id, Some(window));
+    }
+
+    #[test]
+    fn hovering_replaces_unpinned_tooltip() {
+        let mut bar = bar();
+        bar.tooltip_window_id = Some(Id::unique());
+        bar.active_tooltip_id = Some(tooltip_id(false));
+
+        let hovered = tooltip_id(false);
+        let _ = bar.update(Message::TooltipPositionMeasured(hovered.clone()));
+
+        assert_eq!(bar.active_tooltip_id, Some(hovered));
+    }
+}
@@ -1,11 +1,19 @@
-use std::any::Any;
+use std::{
+    any::Any,
+    cell::RefCell,
+    panic::{AssertUnwindSafe, catch_unwind},
+    path::PathBuf,
+    time::{Duration, Instant},
+};
 
 use battery::{service::BatteryService, view::BatteryView};
 use chrono::{DateTime, Local};
+use column_indicator::ColumnIndicatorView;
+use group::GroupView;
 use iced::{
-    Color, Subscription, Task,
-    mouse::ScrollDelta,
-    widget::{self, MouseArea, image},
+    Alignment, Color, Font, Subscription, Task, alignment,
+    widget::{self, Column, MouseArea, Row, Text, image, text},
+    window,
 };
 use label::LabelView;
 use mpris::{
@@ -16,37 +24,59 @@ use niri::{
     service::{NiriEvent, NiriService},
     view::NiriView,
 };
+use now_playing::NowPlayingView;
+use spacer::SpacerView;
 // use system_tray::{service::SystemTrayService, view::SystemTrayView};
+use rustc_hash::{FxHashMap, FxHashSet};
 use time::{service::TimeService, view::TimeView};
+use tracing::{error, warn};
 
 use crate::{
-    Element, Message, MouseEvent,
-    config::{self, Config, ConfigModule, MouseBinds},
+    Element, FIRA_CODE, Message, MouseEvent,
+    config::{self, Config, ConfigModule, IconPosition, MouseBinds},
+    file_watcher::watch_file,
     icon_cache::IconCache,
     modules::audio_visualizer::{
         service::AudioVisualizerService, view::AudioVisualizerView,
     },
+    utils::{animation::HoverAnimations, gesture},
 };
 
 pub mod audio_visualizer;
 pub mod battery;
+pub mod column_indicator;
+pub mod group;
 pub mod label;
 pub mod mpris;
 pub mod niri;
+pub mod now_playing;
+pub mod spacer;
 // pub mod system_tray;
 pub mod time;
 
 #[derive(Debug, Clone)]
 pub enum ModuleMsg {
     Tick(DateTime<Local>),
+    BatteryTick,
+    NowPlayingScroll,
     Niri(NiriEvent),
     AudioSample(Vec<f32>),
     AudioVisualizerGradientUpdate(Option<Vec<Color>>),
     AudioVisualizerTimer,
-    PlayerArtUpdate(String, Option<(image::Handle, Option<Vec<Color>>)>),
+    PlayerArtUpdate(
+        String,
+        String,
+        Option<(image::Handle, Option<Vec<Color>>)>,
+    ),
     Mpris(MprisEvent),
     // Systray(system_tray::service::Event),
     SynchronizeAll,
+    /// The system woke up from suspend
+    Resumed,
+    AnimationFrame,
+    ToggleGroup(widget::Id),
+    /// New contents of a file watched by a label
+    WatchedFile(PathBuf, Option<String>),
     MouseEntered(MouseEvent),
     MouseExited(MouseEvent),
     NoOp,
@@ -61,7 +91,35 @@ pub struct Modules {
     pub time: Option<TimeService>,
     pub niri: Option<NiriService>,
     // pub systray: SystemTrayService,
+    pub hover: HoverAnimations,
+    pub expanded_groups: FxHashSet<widget::Id>,
+    /// Contents of the files watched by labels, `None` until they're read
+    /// or while they're missing
+    pub watched_files: FxHashMap<PathBuf, Option<String>>,
+    pub icon_font: Font,
+    /// Alignment of the lines of tooltip text, see [`Modules::tooltip_text`]
+    pub tooltip_text_align: config::TextAlign,
     pub views: Vec<View>,
+    /// Views that panicked, by address and what they were building, so that
+    /// a view that keeps panicking is only logged once per config
+    panicked: RefCell<FxHashSet<(usize, &'static str)>>,
+}
+
+/// What the configured modules need from the services, collected while
+/// their views are built
+struct ServiceNeeds {
+    battery: bool,
+    battery_interval: Option<Duration>,
+    battery_smoothing: u8,
+    audio_visualizer: bool,
+    follow_player: Option<config::FollowPlayer>,
+    mpris: bool,
+    scroll_interval: Option<Duration>,
+    time: bool,
+    niri: bool,
+    niri_track_layout: bool,
+    niri_socket: Option<String>,
+    watched_files: FxHashSet<PathBuf>,
 }
 
 #[profiling::all_functions]
@@ -74,7 +132,13 @@ impl Modules {
             time: None,
             niri: None,
             // systray: SystemTrayService::new(icon_cache),
+            hover: HoverAnimations::default(),
+            expanded_groups: FxHashSet::default(),
+            watched_files: FxHashMap::default(),
+            icon_font: FIRA_CODE,
+            tooltip_text_align: config::TextAlign::default(),
             views: Vec::new(),
+            panicked: RefCell::default(),
         }
     }
 
@@ -83,101 +147,297 @@ impl Modules {
         config: &mut Config,
         icon_cache: &IconCache,
     ) {
+        let icons_changed = icon_cache.configure(&config.icons);
+        self.hover.set_duration(config.animations.duration);
+        self.icon_font = config.style.icon_font;
+        self.tooltip_text_align = config.tooltips.text_align;
+
         self.views.clear();
-        let mut battery_needed = false;
-        let mut audio_visualizer_needed = false;
-        let mut mpris_needed = false;
-        let mut time_needed = false;
-        let mut niri_needed = false;
+        self.expanded_groups.clear();
+        self.panicked.get_mut().clear();
+        let mut needs = ServiceNeeds {
+            battery: false,
+            battery_interval: None,
+            battery_smoothing: 1,
+            audio_visualizer: false,
+            follow_player: None,
+            mpris: false,
+            scroll_interval: None,
+            time: false,
+            niri: false,
+            niri_track_layout: false,
+            niri_socket: None,
+            watched_files: FxHashSet::default(),
+        };
 
         for (module, position) in config.modules.drain(..) {
-            match module {
-                ConfigModule::Battery(c) => {
-                    battery_needed = true;
-                    self.views.push(Box::new(BatteryView::new(c, position)));
-                }
-                ConfigModule::AudioVisualizer(c) => {
-                    audio_visualizer_needed = true;
-                    self.views
-                        .push(Box::new(AudioVisualizerView::new(c, position)));
-                }
-                ConfigModule::Time(c) => {
-                    time_needed = true;
-                    self.views.push(Box::new(TimeView::new(c, position)));
-                }
-                ConfigModule::Mpris(c) => {
-                    mpris_needed = true;
-                    self.views.push(Box::new(MprisView::new(c, position)));
-                }
-                ConfigModule::Niri(c) => {
-                    niri_needed = true;
-                    self.views.push(Box::new(NiriView::new(*c, position)));
-                }
-                ConfigModule::Label(c) => {
-                    self.views.push(Box::new(LabelView::new(c, position)));
-                }
-                ConfigModule::SystemTray(_c) => {
-                    //     self.views.push(Box::new(SystemTrayView::new(c, position)));
-                }
+            if let Some(view) = self.build_view(module, position, &mut needs) {
+                self.views.push(view);
             }
         }
-        if !battery_needed {
+        warn_unsupported_orientations(&self.views, config.layout.anchor);
+        if !needs.battery {
             self.battery = None;
         } else if self.battery.is_none() {
             self.battery = Some(BatteryService::new());
         }
-        if !audio_visualizer_needed {
+        if let Some(ref mut battery) = self.battery {
+            if let Some(interval) = needs.battery_interval {
+                battery.interval = interval;
+            }
+            battery.smoothing = needs.battery_smoothing.into();
+        }
+        if !needs.audio_visualizer {
             self.audio_visualizer = None;
         } else if self.audio_visualizer.is_none() {
             self.audio_visualizer = Some(AudioVisualizerService::new());
         }
-        if !mpris_needed {
+        if !needs.mpris {
             self.mpris = None;
         } else if self.mpris.is_none() {
             self.mpris = Some(MprisService::new());
         }
-        if !time_needed {
+        if let Some(ref mut mpris) = self.mpris {
+            mpris.scroll_interval = needs.scroll_interval;
+            mpris.follow_player = needs.follow_player.unwrap_or_default();
+        }
+        if !needs.time {
             self.time = None;
         } else if self.time.is_none() {
             self.time = Some(TimeService::new());
         }
-        if !niri_needed {
+        if !needs.niri {
             self.niri = None;
         } else if self.niri.is_none() {
             self.niri = Some(NiriService::new(icon_cache.clone()));
         }
+        if let Some(ref mut niri) = self.niri {
+            niri.track_layout = needs.niri_track_layout;
+            niri.socket = needs.niri_socket;
+            // open windows keep the icons they were mapped with otherwise
+            if icons_changed {
+                niri.remap_windows();
+            }
+        }
+        // files that are still watched keep their contents until the watcher
+        // sends new ones
+        self.watched_files
+            .retain(|path, _| needs.watched_files.contains(path));
+        for path in needs.watched_files {
+            self.watched_files.entry(path).or_default();
+        }
+
+        self.debug_assert_unique_tooltip_ids();
+    }
+
+    /// Creates the view for a configured module, noting which services it
+    /// needs
+    fn build_view(
+        &mut self,
+        module: ConfigModule,
+        position: BarPosition,
+        needs: &mut ServiceNeeds,
+    ) -> Option<View> {
+        Some(match module {
+            ConfigModule::Battery(c) => {
+                needs.battery = true;
+                needs.battery_smoothing =
+                    needs.battery_smoothing.max(c.smoothing);
+                needs.battery_interval = Some(
+                    needs
+                        .battery_interval
+                        .map_or(c.interval, |i: Duration| i.min(c.interval)),
+                );
+                Box::new(BatteryView::new(c, position))
+            }
+            ConfigModule::AudioVisualizer(c) => {
+                needs.audio_visualizer = true;
+                // there is only one gradient, so the first one decides
+                needs
+                    .follow_player
+                    .get_or_insert_with(|| c.follow_player.clone());
+                Box::new(AudioVisualizerView::new(c, position))
+            }
+            ConfigModule::Time(c) => {
+                needs.time = true;
+                Box::new(TimeView::new(c, position))
+            }
+            ConfigModule::Mpris(c) => {
+                needs.mpris = true;
+                Box::new(MprisView::new(c, position))
+            }
+            ConfigModule::NowPlaying(c) => {
+                needs.mpris = true;
+                if c.scroll && c.max_width.is_some() {
+                    needs.scroll_interval = Some(
+                        needs
+                            .scroll_interval
+                            .map_or(c.scroll_interval, |i: Duration| {
+                                i.min(c.scroll_interval)
+                            }),
+                    );
+                }
+                Box::new(NowPlayingView::new(c, position))
+            }
+            ConfigModule::Niri(c) => {
+                needs.niri = true;
+                needs.niri_track_layout |= c.track_layout;
+                needs.niri_socket =
+                    needs.niri_socket.take().or_else(|| c.socket.clone());
+                Box::new(NiriView::new(*c, position))
+            }
+            ConfigModule::ColumnIndicator(c) => {
+                // columns are only known while the layout is tracked
+                needs.niri = true;
+                needs.niri_track_layout = true;
+                Box::new(ColumnIndicatorView::new(c, position))
+            }
+            ConfigModule::Label(c) => {
+                if let Some(path) = &c.watch_file {
+                    needs.watched_files.insert(path.clone());
+                }
+                Box::new(LabelView::new(c, position))
+            }
+            ConfigModule::Spacer(c) => Box::new(SpacerView::new(c, position)),
+            ConfigModule::SystemTray(_c) => {
+                // Box::new(SystemTrayView::new(c, position))
+                return None;
+            }
+            ConfigModule::Group(mut c) => {
+                // the modules of a group are laid out by the group, in
+                // config order, so they share its position
+                let views = std::mem::take(&mut c.modules)
+                    .into_iter()
+                    .filter_map(|module| {
+                        self.build_view(module, position, needs)
+                    })
+                    .collect();
+                let id = widget::Id::unique();
+                if c.expanded {
+                    self.expanded_groups.insert(id.clone());
+                }
+                Box::new(GroupView::new(id, c, views, position))
+            }
+        })
     }
 
     pub fn subscriptions(&self) -> iced::Subscription<Message> {
         Subscription::batch(
             [
                 self.mpris.as_ref().map(|_| MprisService::subscription()),
-                self.niri.as_ref().map(|_| NiriService::subscription()),
-                (self.battery.is_some() || self.time.is_some())
-                    .then(TimeService::subscription),
+                self.mpris
+                    .as_ref()
+                    .and_then(MprisService::scroll_subscription),
+                self.niri.as_ref().map(NiriService::subscription),
+                self.battery.as_ref().map(BatteryService::subscription),
+                self.time.as_ref().map(|_| TimeService::subscription()),
                 self.audio_visualizer
                     .as_ref()
                     .map(AudioVisualizerService::subscription),
+                self.hover.is_animating(Instant::now()).then(|| {
+                    window::frames()
+                        .map(|_| Message::Module(ModuleMsg::AnimationFrame))
+                }),
             ]
             .into_iter()
-            .flatten(),
+            .flatten()
+            .chain(self.watched_files.keys().map(|path| {
+                watch_file(path.clone()).map(|(path, contents)| {
+                    Message::Module(ModuleMsg::WatchedFile(path, contents))
+                })
+            })),
         )
     }
 
     pub fn render_views<'a>(
         &'a self,
         layout: &'a config::Layout,
-    ) -> impl Iterator<Item = (Element<'a>, BarPosition)> + 'a {
+    ) -> impl Iterator<Item = (Element<'a>, BarPosition, f32)> + 'a {
         self.views
             .iter()
-            .map(move |v| (v.view(self, layout), v.position()))
+            .filter(|v| v.supported_orientations().supports(layout.anchor))
+            .filter(|v| !v.is_empty(self))
+            .map(move |v| {
+                (guarded_view(v, self, layout), v.position(), v.margin())
+            })
+    }
+
+    /// Text for a tooltip, with its lines aligned as configured, so that
+    /// all modules' tooltips look the same
+    pub fn tooltip_text<'a>(
+        &self,
+        text: impl text::IntoFragment<'a>,
+        shaping: config::TextShaping,
+    ) -> Text<'a> {
+        Text::new(text)
+            .shaping(shaping.into())
+            .align_x(alignment::Horizontal::from(self.tooltip_text_align))
     }
 
+    /// Runs `f`, catching a panic so that one broken module shows an error
+    /// instead of taking down the whole bar. Views are rebuilt every frame,
+    /// so each panic of `view` is only logged the first time.
+    fn guard<T>(
+        &self,
+        view: &View,
+        what: &'static str,
+        f: impl FnOnce() -> T,
+    ) -> Result<T, ()> {
+        catch_unwind(AssertUnwindSafe(f)).map_err(|payload| {
+            let address = (&raw const **view).cast::<()>().addr();
+            if !self.panicked.borrow_mut().insert((address, what)) {
+                return;
+            }
+            let reason = payload
+                .downcast_ref::<&str>()
+                .copied()
+                .or_else(|| {
+                    payload.downcast_ref::<String>().map(String::as_str)
+                })
+                .unwrap_or("unknown panic");
+            error!(
+                "{} module panicked building its {what}: {reason}",
+                view.name()
+            );
+        })
+    }
+
+    /// The tooltip of the first view, in config order, that has one for
+    /// `id`. Ids are unique, so there should never be a second one.
     pub fn render_tooltip_for_id<'a>(
         &'a self,
         id: &widget::Id,
     ) -> Option<Element<'a>> {
-        self.views.iter().find_map(|view| view.tooltip(self, id))
+        self.views.iter().find_map(|view| {
+            self.guard(view, "tooltip", || view.tooltip(self, id))
+                .unwrap_or_else(|()| {
+                    // a panicking view only answers for its own tooltips
+                    view.tooltip_ids()
+                        .contains(id)
+                        .then(|| Text::new("failed to show tooltip").into())
+                })
+        })
+    }
+
+    /// Two views using the same tooltip id would show each other's tooltips
+    fn debug_assert_unique_tooltip_ids(&self) {
+        if !cfg!(debug_assertions) {
+            return;
+        }
+        let mut seen = FxHashSet::default();
+        for view in &self.views {
+            for id in view.tooltip_ids() {
+                debug_assert!(
+                    seen.insert(id.clone()),
+                    "tooltip id {id:?} of {} is already in use",
+                    view.name()
+                );
+            }
+        }
+    }
+
+    pub fn tooltip_is_interactive(&self, id: &widget::Id) -> bool {
+        self.views.iter().any(|view| view.interactive_tooltip(id))
     }
 
     pub fn render_menu_for_id<'a>(
@@ -191,19 +451,30 @@ impl Modules {
     pub fn update(&mut self, message: ModuleMsg) -> ModuleAction {
         'msg: {
             match message {
-                ModuleMsg::MouseEntered(event) => {
-                    let Some(ref mut niri) = self.niri else {
-                        break 'msg;
-                    };
-                    let MouseEvent::Workspace(id) = event;
-                    niri.hovered_workspace_id = Some(id);
-                }
-                ModuleMsg::MouseExited(_event) => {
-                    let Some(ref mut niri) = self.niri else {
-                        break 'msg;
-                    };
-                    niri.hovered_workspace_id = None;
-                }
+                ModuleMsg::MouseEntered(event) => match event {
+                    MouseEvent::Workspace(id) => {
+                        let Some(ref mut niri) = self.niri else {
+                            break 'msg;
+                        };
+                        niri.hovered_workspace_id = Some(id);
+                    }
+                    MouseEvent::Label(id) => self.hover.set_hovered(id, true),
+                    MouseEvent::Group(id) => {
+                        self.expanded_groups.insert(id);
+                    }
+                },
+                ModuleMsg::MouseExited(event) => match event {
+                    MouseEvent::Workspace(_) => {
+                        let Some(ref mut niri) = self.niri else {
+                            break 'msg;
+                        };
+                        niri.hovered_workspace_id = None;
+                    }
+                    MouseEvent::Label(id) => self.hover.set_hovered(id, false),
+                    MouseEvent::Group(id) => {
+                        self.expanded_groups.remove(&id);
+                    }
+                },
                 ModuleMsg::Tick(date_time) => {
                     if let Some(ref mut time) = self.time {
                         time.update(date_time);
@@ -211,9 +482,18 @@ impl Modules {
                             view.as_any().is::<TimeView>()
                         });
                     }
-                    if let Some(ref mut battery) = self.battery {
-                        battery.fetch_battery_info();
-                    }
+                }
+                ModuleMsg::NowPlayingScroll => {
+                    let Some(ref mut mpris) = self.mpris else {
+                        break 'msg;
+                    };
+                    mpris.scroll = mpris.scroll.wrapping_add(1);
+                }
+                ModuleMsg::BatteryTick => {
+                    let Some(ref mut battery) = self.battery else {
+                        break 'msg;
+                    };
+                    battery.fetch_battery_info();
                 }
                 ModuleMsg::Niri(event) => {
                     let Some(ref mut niri) = self.niri else {
@@ -249,26 +529,11 @@ impl Modules {
                     });
                     return task;
                 }
-                ModuleMsg::PlayerArtUpdate(player_name, art) => {
+                ModuleMsg::PlayerArtUpdate(player_name, art_url, art) => {
                     let Some(ref mut mpris) = self.mpris else {
                         break 'msg;
                     };
-                    if let Some((_, player)) = mpris
-                        .players
-                        .iter_mut()
-                        .find(|(name, _)| *name == player_name)
-                        && let Some((art, gradient)) = art
-                    {
-                        player.art = Some(art);
-                        player.colors.clone_from(&gradient);
-                        if player.status == "Playing" {
-                            let captured_colors = gradient;
-                            return ModuleAction::Task(iced::Task::perform(
-                                async move { captured_colors },
-                                ModuleMsg::AudioVisualizerGradientUpdate,
-                            ));
-                        }
-                    }
+                    return mpris.set_art(&player_name, &art_url, art);
                 }
                 // ModuleMsg::Systray(event) => {
                 //     self.systray.update(event);
@@ -279,6 +544,27 @@ impl Modules {
                 ModuleMsg::SynchronizeAll => {
                     self.synchronize_views();
                 }
+                ModuleMsg::Resumed => {
+                    if let Some(ref mut battery) = self.battery {
+                        battery.fetch_battery_info();
+                    }
+                    if let Some(ref mut time) = self.time {
+                        time.update(Local::now());
+                    }
+                    self.synchronize_views();
+                }
+                // only here to redraw while a transition is running
+                ModuleMsg::AnimationFrame => {}
+                ModuleMsg::WatchedFile(path, contents) => {
+                    if let Some(watched) = self.watched_files.get_mut(&path) {
+                        *watched = contents;
+                    }
+                }
+                ModuleMsg::ToggleGroup(id) => {
+                    if !self.expanded_groups.remove(&id) {
+                        self.expanded_groups.insert(id);
+                    }
+                }
                 ModuleMsg::AudioVisualizerTimer => {
                     let Some(ref mut audio_visualizer) = self.audio_visualizer
                     else {
@@ -298,13 +584,15 @@ impl Modules {
             let view = unsafe { &mut *(&raw mut self.views[i]) };
             view.synchronize(unsafe { &*(self as *const _) });
         }
+        self.debug_assert_unique_tooltip_ids();
     }
 
     #[allow(clippy::deref_addrof, clippy::ref_as_ptr)]
     fn synchronize_views_filtered(&mut self, filter: fn(&View) -> bool) {
         for i in 0..self.views.len() {
             let view = unsafe { &mut *(&raw mut self.views[i]) };
-            if filter(view) {
+            // groups pass the synchronization on to their modules
+            if filter(view) || view.as_any().is::<GroupView>() {
                 view.synchronize(unsafe { &*(self as *const _) });
             }
         }
@@ -322,6 +610,23 @@ pub struct BarPosition {
     pub align: BarAlignment,
 }
 
+#[derive(PartialEq, Eq, Debug, Clone, Copy)]
+pub enum Orientations {
+    Both,
+    Horizontal,
+    Vertical,
+}
+
+impl Orientations {
+    pub fn supports(self, anchor: config::Anchor) -> bool {
+        match self {
+            Orientations::Both => true,
+            Orientations::Horizontal => !anchor.vertical(),
+            Orientations::Vertical => anchor.vertical(),
+        }
+    }
+}
+
 #[derive(PartialEq, Eq, Debug, Clone, Copy)]
 pub enum BarAlignment {
     Start,
@@ -338,6 +643,25 @@ pub trait ViewTrait<M>: Any {
 
     fn position(&self) -> BarPosition;
 
+    /// Name of the module in the config, for messages about it
+    fn name(&self) -> &'static str;
+
+    /// Views on a bar of an orientation they don't support are not shown
+    fn supported_orientations(&self) -> Orientations {
+        Orientations::Both
+    }
+
+    /// Space to keep around this view, see [`crate::utils::spacing`]
+    fn margin(&self) -> f32 {
+        0.0
+    }
+
+    /// Views with nothing to show are left out of their section, so that
+    /// their margin and the section spacing around them don't leave a gap
+    fn is_empty(&self, _modules: &M) -> bool {
+        false
+    }
+
     fn tooltip<'a>(
         &'a self,
         _modules: &'a M,
@@ -354,11 +678,55 @@ pub trait ViewTrait<M>: Any {
         None
     }
 
+    /// Ids of the containers this view shows tooltips for. Each has to be
+    /// unique across all views, see [`Modules::render_tooltip_for_id`].
+    fn tooltip_ids(&self) -> Vec<widget::Id> {
+        Vec::new()
+    }
+
+    /// Whether the tooltip with this id contains widgets that need to receive
+    /// input, in which case it stays open while hovered
+    fn interactive_tooltip(&self, _id: &widget::Id) -> bool {
+        false
+    }
+
     fn synchronize(&mut self, _modules: &M) {}
 
     fn as_any(&self) -> &dyn Any;
 }
 
+/// Builds the element of `view`, or an error placeholder if it panics
+fn guarded_view<'a>(
+    view: &'a View,
+    modules: &'a Modules,
+    layout: &'a config::Layout,
+) -> Element<'a> {
+    modules
+        .guard(view, "view", || view.view(modules, layout))
+        .unwrap_or_else(|()| Text::new("!").style(widget::text::danger).into())
+}
+
+/// Views on a bar of an orientation they don't support are left out, which is
+/// easy to miss, so it's logged. Modules in groups are checked too.
+fn warn_unsupported_orientations(views: &[View], anchor: config::Anchor) {
+    for view in views {
+        if let Some(group) = view.as_any().downcast_ref::<GroupView>() {
+            warn_unsupported_orientations(group.views(), anchor);
+        }
+        if !view.supported_orientations().supports(anchor) {
+            warn!(
+                "the {} module does not support {} bars and will not be shown",
+                view.name(),
+                if anchor.vertical() {
+                    "vertical"
+                } else {
+                    "horizontal"
+                }
+            );
+        }
+    }
+}
+
 #[profiling::function]
 pub fn mouse_binds<'a>(
     element: impl Into<Element<'a>>,
@@ -374,56 +742,63 @@ pub fn mouse_binds<'a>(
     }
 
     if let Some(left) = &binds.mouse_left {
-        mouse_area = mouse_area.on_release(left.clone());
+        mouse_area = mouse_area.on_release(gesture::click(left.clone()));
     }
 
     if let Some(double) = &binds.double_click {
-        mouse_area = mouse_area.on_double_click(double.clone());
+        mouse_area = mouse_area.on_double_click(gesture::click(double.clone()));
     }
 
     if let Some(right) = &binds.mouse_right {
-        mouse_area = mouse_area.on_right_release(right.clone());
+        mouse_area = mouse_area.on_right_release(gesture::click(right.clone()));
     }
 
     if let Some(middle) = &binds.mouse_middle {
-        mouse_area = mouse_area.on_middle_release(middle.clone());
+        mouse_area =
+            mouse_area.on_middle_release(gesture::click(middle.clone()));
     }
 
     if let Some(ref scroll) = binds.scroll {
-        mouse_area = mouse_area.on_scroll(|delta| {
-            let (x, y) = match delta {
-                ScrollDelta::Lines { x, y } | ScrollDelta::Pixels { x, y } => {
-                    (x, y)
-                }
-            };
-
-            if y > 0.0
-                && let Some(up) = scroll.up.clone()
-            {
-                up
-            } else if y < 0.0
-                && let Some(down) = scroll.down.clone()
-            {
-                down
-            } else if x < 0.0
-                && let Some(right) = scroll.right.clone()
-            {
-                right
-            } else if x > 0.0
-                && let Some(left) = scroll.left.clone()
-            {
-                left
-            } else {
-                Message::NoOp
-            }
-        });
+        mouse_area = mouse_area
+            .on_scroll(|delta| gesture::scroll(delta, scroll.clone()));
     }
 
     mouse_area.into()
 }
 
+/// Places `icon` next to `text` as configured. `before` and `after` follow
+/// the direction of the bar, while `above` and `below` always stack.
+pub fn icon_with_text<'a>(
+    icon: impl Into<Element<'a>>,
+    text: impl Into<Element<'a>>,
+    position: IconPosition,
+    layout: &config::Layout,
+) -> Element<'a> {
+    let (first, second, stacked) = match position {
+        IconPosition::Before => (icon.into(), text.into(), false),
+        IconPosition::After => (text.into(), icon.into(), false),
+        IconPosition::Above => (icon.into(), text.into(), true),
+        IconPosition::Below => (text.into(), icon.into(), true),
+    };
+
+    if stacked || layout.anchor.vertical() {
+        Column::new()
+            .push(first)
+            .push(second)
+            .align_x(Alignment::Center)
+            .into()
+    } else {
+        Row::new()
+            .push(first)
+            .push(second)
+            .align_y(Alignment::Center)
+            .into()
+    }
+}
+
 #[profiling::function]
 pub fn process_command(cmd: &config::Command) -> Message {
+    let throttle = cmd.throttle.map(Duration::from_millis);
     if cmd.args.is_empty() {
         Message::NoOp
     } else if let Some(sh) = cmd.sh
@@ -432,19 +807,23 @@ pub fn process_command(cmd: &config::Command) -> Message {
         Message::Command(CommandSpec {
             command: String::from("sh"),
             args: Some(vec![String::from("-c"), cmd.args[0].clone()]),
+            throttle,
         })
     } else {
         Message::Command(CommandSpec {
             command: cmd.args[0].clone(),
             args: cmd.args.get(1..).map(<[String]>::to_vec),
+            throttle,
         })
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct CommandSpec {
     pub command: String,
     pub args: Option<Vec<String>>,
+    /// Spawns of the command closer together than this are dropped
+    pub throttle: Option<Duration>,
 }
 
 impl std::fmt::Display for CommandSpec {
@@ -462,3 +841,136 @@ impl std::fmt::Display for CommandSpec {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::Cell, rc::Rc};
+
+    use iced::widget::Column;
+
+    use super::*;
+
+    struct StubView {
+        empty: Rc<Cell<bool>>,
+        margin: f32,
+        position: BarPosition,
+        panics: bool,
+        tooltip_id: widget::Id,
+    }
+
+    impl ViewTrait<Modules> for StubView {
+        fn view<'a>(
+            &'a self,
+            _modules: &'a Modules,
+            _layout: &'a config::Layout,
+        ) -> Element<'a> {
+            assert!(!self.panics, "stub view is broken");
+            Column::new().into()
+        }
+
+        fn tooltip<'a>(
+            &'a self,
+            _modules: &'a Modules,
+            _id: &widget::Id,
+        ) -> Option<Element<'a>> {
+            assert!(!self.panics, "stub tooltip is broken");
+            None
+        }
+
+        fn tooltip_ids(&self) -> Vec<widget::Id> {
+            vec![self.tooltip_id.clone()]
+        }
+
+        fn position(&self) -> BarPosition {
+            self.position
+        }
+
+        fn name(&self) -> &'static str {
+            "stub"
+        }
+
+        fn margin(&self) -> f32 {
+            self.margin
+        }
+
+        fn is_empty(&self, _modules: &Modules) -> bool {
+            self.empty.get()
+        }
+
+        fn as_any(&self) -> &dyn Any {
+            self
+        }
+    }
+
+    #[test]
+    fn empty_views_leave_the_layout() {
+        let layout = config::Layout::default();
+        let empty = Rc::new(Cell::new(false));
+        let mut modules = Modules::new();
+        for (idx, margin) in [0.0, 1.0, 2.0].into_iter().enumerate() {
+            modules.views.push(Box::new(StubView {
+                empty: if idx == 1 {
+                    empty.clone()
+                } else {
+                    Rc::new(Cell::new(false))
+                },
+                margin,
+                position: BarPosition {
+                    idx,
+                    align: BarAlignment::Start,
+                },
+                panics: false,
+                tooltip_id: widget::Id::unique(),
+            }));
+        }
+        let margins = |modules: &Modules| -> Vec<f32> {
+            modules
+                .render_views(&layout)
+                .map(|(_, _, margin)| margin)
+                .collect()
+        };
+
+        assert_eq!(margins(&modules), [0.0, 1.0, 2.0]);
+
+        empty.set(true);
+        assert_eq!(margins(&modules), [0.0, 2.0]);
+
+        empty.set(false);
+        assert_eq!(margins(&modules), [0.0, 1.0, 2.0]);
+    }
+
+    #[test]
+    fn panicking_views_are_contained() {
+        let layout = config::Layout::default();
+        let mut modules = Modules::new();
+        let broken_tooltip = widget::Id::unique();
+        for (idx, panics) in [false, true].into_iter().enumerate() {
+            modules.views.push(Box::new(StubView {
+                empty: Rc::new(Cell::new(false)),
+                margin: 0.0,
+                position: BarPosition {
+                    idx,
+                    align: BarAlignment::Start,
+                },
+                panics,
+                tooltip_id: if panics {
+                    broken_tooltip.clone()
+                } else {
+                    widget::Id::unique()
+                },
+            }));
+        }
+
+        assert_eq!(modules.render_views(&layout).count(), 2);
+        assert_eq!(modules.render_views(&layout).count(), 2);
+        // logged once, however often it's rendered
+        assert_eq!(modules.panicked.borrow().len(), 1);
+        assert!(modules.render_tooltip_for_id(&broken_tooltip).is_some());
+        // the broken view doesn't take over the tooltips of other views
+        assert!(
+            modules
+                .render_tooltip_for_id(&widget::Id::unique())
+                .is_none()
+        );
+    }
+}
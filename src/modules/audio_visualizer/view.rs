@@ -8,7 +8,7 @@ use iced::{
 use super::service::AudioVisualizerService;
 use crate::{
     Element,
-    config::{self, FloatOrPercent},
+    config::{self, FloatOrPercent, Grow},
     modules::{BarPosition, Modules, ViewTrait, mouse_binds},
     utils::style::container_style,
 };
@@ -64,6 +64,14 @@ impl ViewTrait<Modules> for AudioVisualizerView {
         self.position
     }
 
+    fn name(&self) -> &'static str {
+        "audio-visualizer"
+    }
+
+    fn margin(&self) -> f32 {
+        self.config.style.margin
+    }
+
     fn as_any(&self) -> &dyn Any {
         self
     }
@@ -149,6 +157,31 @@ impl<Message> canvas::Program<Message> for AudioVisualizerCanvas<'_> {
                     }
                     .unwrap_or(&self.config.color);
 
+                    if self.config.grow != Grow::Center {
+                        let width = (left_width + right_width) / 2.0;
+                        if width > 0.0 {
+                            let cross = if self.config.grow == Grow::Top {
+                                0.0
+                            } else {
+                                max_bar_width * 2.0 - width
+                            };
+                            let (top_left, bar_size) = if self.vertical {
+                                (
+                                    Point { x: cross, y: pos },
+                                    Size::new(width, bar_thickness),
+                                )
+                            } else {
+                                (
+                                    Point { x: pos, y: cross },
+                                    Size::new(bar_thickness, width),
+                                )
+                            };
+                            frame
+                                .fill_rectangle(top_left, bar_size, *bar_color);
+                        }
+                        continue;
+                    }
+
                     if left_val > 0.0 {
                         let (top_left, bar_size) = if self.vertical {
                             (
@@ -1,11 +1,20 @@
+use std::{collections::VecDeque, time::Duration};
+
+use iced::{Subscription, time};
 use tracing::error;
 
+use crate::{Message, modules};
+
 extern crate starship_battery as battery;
 
 #[derive(Debug, Clone)]
 pub struct BatteryInfo {
     pub percentage: f32,
     pub state: battery::State,
+    /// Full charge relative to the design capacity, if the battery reports
+    /// its design capacity
+    pub health: Option<f32>,
+    pub cycles: Option<u32>,
 }
 
 pub struct BatteryService {
@@ -14,6 +23,10 @@ pub struct BatteryService {
     pub avg_percentage: f32,
     pub is_charging: bool,
     pub is_empty: bool,
+    pub interval: Duration,
+    /// Number of readings averaged into `avg_percentage`
+    pub smoothing: usize,
+    readings: VecDeque<f32>,
 }
 
 #[profiling::all_functions]
@@ -32,6 +45,9 @@ impl BatteryService {
             avg_percentage: 0.0,
             is_charging: false,
             is_empty: true,
+            interval: Duration::from_secs(30),
+            smoothing: 1,
+            readings: VecDeque::new(),
         };
 
         new.fetch_battery_info();
@@ -39,6 +55,11 @@ impl BatteryService {
         new
     }
 
+    pub fn subscription(&self) -> Subscription<Message> {
+        time::every(self.interval)
+            .map(|_| Message::Module(modules::ModuleMsg::BatteryTick))
+    }
+
     pub fn fetch_battery_info(&mut self) {
         let Some(manager) = &self.manager else {
             return error!("No battery manager");
@@ -60,9 +81,14 @@ impl BatteryService {
                 return error!("battery: {e}");
             }
 
+            let health: f32 =
+                (bat.energy_full() / bat.energy_full_design()).into();
             info.push(BatteryInfo {
                 percentage: (bat.energy() / bat.energy_full()).into(),
                 state: bat.state(),
+                // batteries without a design capacity report it as zero
+                health: (health.is_finite() && health > 0.0).then_some(health),
+                cycles: bat.cycle_count(),
             });
         }
 
@@ -70,7 +96,7 @@ impl BatteryService {
 
         let total_percentage: f32 =
             self.batteries.iter().map(|b| b.percentage).sum();
-        self.avg_percentage = total_percentage / self.batteries.len() as f32;
+        self.smooth(total_percentage / self.batteries.len() as f32);
 
         self.is_charging = self.batteries.iter().all(|b| {
             !matches!(
@@ -81,4 +107,19 @@ impl BatteryService {
 
         self.is_empty = self.batteries.is_empty();
     }
+
+    /// Average the latest readings, so that a charge fluctuating around a
+    /// boundary doesn't make the displayed value jitter
+    fn smooth(&mut self, reading: f32) {
+        if reading.is_nan() {
+            self.avg_percentage = reading;
+            return;
+        }
+        self.readings.push_back(reading);
+        while self.readings.len() > self.smoothing.max(1) {
+            self.readings.pop_front();
+        }
+        self.avg_percentage =
+            self.readings.iter().sum::<f32>() / self.readings.len() as f32;
+    }
 }
@@ -8,7 +8,10 @@ use tracing::warn;
 
 use crate::{
     Element, config,
-    modules::{BarPosition, Modules, ViewTrait, mouse_binds},
+    modules::{
+        BarPosition, Modules, ViewTrait, battery::service::BatteryInfo,
+        icon_with_text, mouse_binds,
+    },
     utils::style::container_style,
 };
 extern crate starship_battery as battery;
@@ -23,10 +26,10 @@ pub struct BatteryView {
 impl ViewTrait<Modules> for BatteryView {
     fn view<'a>(
         &'a self,
-        service: &'a Modules,
+        modules: &'a Modules,
         layout: &'a config::Layout,
     ) -> Element<'a> {
-        let service = service
+        let service = modules
             .battery
             .as_ref()
             .expect("battery should not be None");
@@ -42,55 +45,107 @@ impl ViewTrait<Modules> for BatteryView {
                 .color(self.config.charging_color)
         } else {
             Text::new(icon).size(self.config.icon_size)
-        };
+        }
+        .font(modules.icon_font)
+        .shaping(self.config.icon_style.text_shaping.into());
+
+        let icon_widget = container_style(
+            Container::new(icon_text),
+            &self.config.icon_style,
+            layout,
+        );
 
-        let mut icon_widget = Container::new(icon_text);
-        icon_widget = container_style(icon_widget, &self.config.style, layout)
-            .id(self.id.clone());
+        let content: Element =
+            if self.config.show_percentage && !layout.compact() {
+                let percentage = Text::new(format_percentage(
+                    service.avg_percentage,
+                    self.config.precision,
+                    self.config.round_to,
+                ))
+                .size(self.config.text_size)
+                .shaping(self.config.text_style.text_shaping.into());
+                let text_widget = container_style(
+                    Container::new(percentage),
+                    &self.config.text_style,
+                    layout,
+                );
+
+                icon_with_text(
+                    icon_widget,
+                    text_widget,
+                    self.config.icon_position,
+                    layout,
+                )
+            } else {
+                icon_widget.into()
+            };
+
+        let mut content = container_style(
+            Container::new(content),
+            &self.config.style,
+            layout,
+        )
+        .id(self.id.clone());
 
         if layout.anchor.vertical() {
-            icon_widget = icon_widget.center_x(Length::Fill);
+            content = content.center_x(Length::Fill);
         } else {
-            icon_widget = icon_widget.center_y(Length::Fill);
+            content = content.center_y(Length::Fill);
         }
 
-        mouse_binds(icon_widget, &self.config.binds, Some(self.id.clone()))
+        let tooltip_id = self.config.show_tooltip.then(|| self.id.clone());
+        mouse_binds(content, &self.config.binds, tooltip_id)
     }
 
     fn position(&self) -> BarPosition {
         self.position
     }
 
+    fn name(&self) -> &'static str {
+        "battery"
+    }
+
+    fn margin(&self) -> f32 {
+        self.config.style.margin
+    }
+
+    fn is_empty(&self, modules: &Modules) -> bool {
+        modules.battery.as_ref().is_none_or(|b| b.is_empty)
+    }
+
+    fn tooltip_ids(&self) -> Vec<widget::Id> {
+        self.config
+            .show_tooltip
+            .then(|| self.id.clone())
+            .into_iter()
+            .collect()
+    }
+
     fn tooltip<'a>(
         &'a self,
         modules: &'a Modules,
         id: &widget::Id,
     ) -> Option<Element<'a>> {
-        if *id != self.id {
+        if *id != self.id || !self.config.show_tooltip {
             return None;
         }
         let service = modules
             .battery
             .as_ref()
             .expect("battery should not be None");
+        let text = service
+            .batteries
+            .iter()
+            .enumerate()
+            .map(|(i, bat)| {
+                format_tooltip(&self.config.tooltip_format, i + 1, bat)
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
         Some(
-            Text::new(
-                service
-                    .batteries
-                    .iter()
-                    .enumerate()
-                    .map(|(i, bat)| {
-                        format!(
-                            "Battery {}: {}% ({})",
-                            i + 1,
-                            (bat.percentage * 100.0).floor(),
-                            bat.state
-                        )
-                    })
-                    .collect::<Vec<_>>()
-                    .join("\n"),
-            )
-            .into(),
+            modules
+                .tooltip_text(text, self.config.style.text_shaping)
+                .into(),
         )
     }
 
@@ -110,6 +165,56 @@ impl BatteryView {
     }
 }
 
+#[profiling::function]
+fn format_percentage(
+    percentage: f32,
+    precision: u8,
+    round_to: Option<u8>,
+) -> String {
+    let percentage = percentage * 100.0;
+    match round_to {
+        Some(step) => {
+            let step = f32::from(step);
+            format!("{}%", (percentage / step).round() * step)
+        }
+        None => format!("{percentage:.*}%", usize::from(precision)),
+    }
+}
+
+/// Fill in the tokens of `format` for one battery. Lines with a token the
+/// battery doesn't report, such as `{health}` without a design capacity, are
+/// left out.
+#[profiling::function]
+fn format_tooltip(format: &str, index: usize, bat: &BatteryInfo) -> String {
+    let percentage = (bat.percentage * 100.0).floor().to_string();
+    let health = bat.health.map(|h| (h * 100.0).floor().to_string());
+    let cycles = bat.cycles.map(|c| c.to_string());
+    let tokens = [
+        ("{index}", Some(index.to_string())),
+        ("{percentage}", Some(percentage)),
+        ("{state}", Some(bat.state.to_string())),
+        ("{health}", health),
+        ("{cycles}", cycles),
+    ];
+
+    format
+        .lines()
+        .filter_map(|line| {
+            tokens
+                .iter()
+                .try_fold(
+                    line.to_string(),
+                    |line, (token, value)| match value {
+                        Some(value) => Some(line.replace(token, value)),
+                        None if line.contains(token) => None,
+                        None => Some(line),
+                    },
+                )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
 #[profiling::function]
 fn get_battery_icon(percentage: f32) -> &'static str {
     match percentage {
@@ -130,3 +235,44 @@ fn get_battery_icon(percentage: f32) -> &'static str {
         _ => unreachable!(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn percentage_precision() {
+        assert_eq!(format_percentage(0.8449, 0, None), "84%");
+        assert_eq!(format_percentage(0.8451, 0, None), "85%");
+        assert_eq!(format_percentage(0.8449, 1, None), "84.5%");
+    }
+
+    #[test]
+    fn tooltip_leaves_out_unreported_lines() {
+        let format = "Battery {index}: {percentage}%\nHealth: {health}%\n{cycles} cycles";
+        let mut bat = BatteryInfo {
+            percentage: 0.845,
+            state: battery::State::Discharging,
+            health: Some(0.912),
+            cycles: None,
+        };
+        assert_eq!(
+            format_tooltip(format, 1, &bat),
+            "Battery 1: 84%\nHealth: 91%"
+        );
+
+        bat.health = None;
+        bat.cycles = Some(321);
+        assert_eq!(
+            format_tooltip(format, 2, &bat),
+            "Battery 2: 84%\n321 cycles"
+        );
+    }
+
+    #[test]
+    fn percentage_round_to() {
+        assert_eq!(format_percentage(0.84, 0, Some(5)), "85%");
+        assert_eq!(format_percentage(0.82, 0, Some(5)), "80%");
+        assert_eq!(format_percentage(0.82, 2, Some(10)), "80%");
+    }
+}
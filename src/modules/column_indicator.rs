@@ -0,0 +1,85 @@
+use std::any::Any;
+
+use iced::{
+    Length,
+    widget::{Column, Container, Text},
+};
+
+use crate::{
+    Element, config,
+    modules::{BarPosition, Modules, ViewTrait, mouse_binds},
+    utils::style::container_style,
+};
+
+/// Shows which column of niri's scrolling layout the focused window is in
+pub struct ColumnIndicatorView {
+    config: config::ColumnIndicator,
+    pub position: BarPosition,
+}
+
+#[profiling::all_functions]
+impl ViewTrait<Modules> for ColumnIndicatorView {
+    fn view<'a>(
+        &'a self,
+        modules: &'a Modules,
+        layout: &'a config::Layout,
+    ) -> Element<'a> {
+        let service = modules.niri.as_ref().expect("niri should not be None");
+        let Some((column, columns)) = service.focused_column() else {
+            return Column::new().into();
+        };
+
+        let text = self
+            .config
+            .format
+            .replace("{column}", &column.to_string())
+            .replace("{columns}", &columns.to_string());
+
+        let mut content = container_style(
+            Container::new(
+                Text::new(text)
+                    .size(self.config.size)
+                    .shaping(self.config.style.text_shaping.into()),
+            ),
+            &self.config.style,
+            layout,
+        );
+
+        if layout.anchor.vertical() {
+            content = content.center_x(Length::Fill);
+        } else {
+            content = content.center_y(Length::Fill);
+        }
+
+        mouse_binds(content, &self.config.binds, None)
+    }
+
+    fn position(&self) -> BarPosition {
+        self.position
+    }
+
+    fn name(&self) -> &'static str {
+        "column-indicator"
+    }
+
+    fn margin(&self) -> f32 {
+        self.config.style.margin
+    }
+
+    fn is_empty(&self, modules: &Modules) -> bool {
+        modules
+            .niri
+            .as_ref()
+            .is_none_or(|niri| niri.focused_column().is_none())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl ColumnIndicatorView {
+    pub fn new(config: config::ColumnIndicator, position: BarPosition) -> Self {
+        Self { config, position }
+    }
+}
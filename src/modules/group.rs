@@ -0,0 +1,184 @@
+use std::any::Any;
+
+use iced::{
+    Alignment, Length,
+    widget::{self, Column, Container, MouseArea, Row, Text},
+};
+
+use crate::{
+    Element, Message, MouseEvent,
+    config::{self, ExpandOn, MouseBinds},
+    modules::{
+        BarPosition, ModuleMsg, Modules, View, ViewTrait, guarded_view,
+        mouse_binds,
+    },
+    utils::style::container_style,
+};
+
+/// Modules behind an icon, only shown while the group is expanded
+pub struct GroupView {
+    id: widget::Id,
+    config: config::Group,
+    /// The group's binds, with the left click toggling the group if it
+    /// expands on click
+    binds: MouseBinds,
+    views: Vec<View>,
+    pub position: BarPosition,
+}
+
+#[profiling::all_functions]
+impl ViewTrait<Modules> for GroupView {
+    fn view<'a>(
+        &'a self,
+        modules: &'a Modules,
+        layout: &'a config::Layout,
+    ) -> Element<'a> {
+        let expanded = modules.expanded_groups.contains(&self.id);
+
+        let icon = if expanded {
+            &self.config.expanded_icon
+        } else {
+            &self.config.icon
+        };
+        let mut icon = container_style(
+            Container::new(
+                Text::new(icon)
+                    .size(self.config.size)
+                    .font(modules.icon_font)
+                    .shaping(self.config.style.text_shaping.into()),
+            ),
+            &self.config.style,
+            layout,
+        );
+        if layout.anchor.vertical() {
+            icon = icon.center_x(Length::Fill);
+        } else {
+            icon = icon.center_y(Length::Fill);
+        }
+        let icon = mouse_binds(icon, &self.binds, None);
+
+        let mut children = vec![icon];
+        if expanded {
+            children.extend(
+                self.views
+                    .iter()
+                    .filter(|view| {
+                        view.supported_orientations().supports(layout.anchor)
+                            && !view.is_empty(modules)
+                    })
+                    .map(|view| guarded_view(view, modules, layout)),
+            );
+        }
+
+        let spacing = self
+            .config
+            .spacing
+            .unwrap_or_else(|| layout.section_spacing());
+        let content: Element = if layout.anchor.vertical() {
+            Column::with_children(children)
+                .spacing(spacing)
+                .align_x(Alignment::Center)
+                .into()
+        } else {
+            Row::with_children(children)
+                .spacing(spacing)
+                .align_y(Alignment::Center)
+                .into()
+        };
+
+        if self.config.expand_on == ExpandOn::Hover {
+            MouseArea::new(content)
+                .on_enter(Message::Module(ModuleMsg::MouseEntered(
+                    MouseEvent::Group(self.id.clone()),
+                )))
+                .on_exit(Message::Module(ModuleMsg::MouseExited(
+                    MouseEvent::Group(self.id.clone()),
+                )))
+                .into()
+        } else {
+            content
+        }
+    }
+
+    fn position(&self) -> BarPosition {
+        self.position
+    }
+
+    fn name(&self) -> &'static str {
+        "group"
+    }
+
+    fn margin(&self) -> f32 {
+        self.config.style.margin
+    }
+
+    /// A group of modules that are all empty would only show its icon
+    fn is_empty(&self, modules: &Modules) -> bool {
+        self.views.iter().all(|view| view.is_empty(modules))
+    }
+
+    fn tooltip<'a>(
+        &'a self,
+        modules: &'a Modules,
+        id: &widget::Id,
+    ) -> Option<Element<'a>> {
+        self.views.iter().find_map(|view| view.tooltip(modules, id))
+    }
+
+    fn menu<'a>(
+        &'a self,
+        modules: &'a Modules,
+        id: &widget::Id,
+    ) -> Option<Element<'a>> {
+        self.views.iter().find_map(|view| view.menu(modules, id))
+    }
+
+    fn tooltip_ids(&self) -> Vec<widget::Id> {
+        self.views
+            .iter()
+            .flat_map(|view| view.tooltip_ids())
+            .collect()
+    }
+
+    fn interactive_tooltip(&self, id: &widget::Id) -> bool {
+        self.views.iter().any(|view| view.interactive_tooltip(id))
+    }
+
+    fn synchronize(&mut self, modules: &Modules) {
+        for view in &mut self.views {
+            view.synchronize(modules);
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl GroupView {
+    /// `views` are the group's modules, which have been taken out of
+    /// `config`
+    pub fn new(
+        id: widget::Id,
+        config: config::Group,
+        views: Vec<View>,
+        position: BarPosition,
+    ) -> Self {
+        let mut binds = config.binds.clone();
+        if config.expand_on == ExpandOn::Click {
+            binds.mouse_left =
+                Some(Message::Module(ModuleMsg::ToggleGroup(id.clone())));
+        }
+        Self {
+            id,
+            config,
+            binds,
+            views,
+            position,
+        }
+    }
+
+    pub fn views(&self) -> &[View] {
+        &self.views
+    }
+}
@@ -1,15 +1,15 @@
-use std::any::Any;
+use std::{any::Any, time::Instant};
 
 use iced::{
-    Length,
-    widget::{self, Container, Text, text},
+    Alignment, Length, Theme,
+    widget::{self, Column, Container, MouseArea, container, text},
 };
 
 use super::mouse_binds;
 use crate::{
-    Element, config,
-    modules::{BarPosition, Modules, ViewTrait},
-    utils::style::container_style,
+    Element, Message, MouseEvent, config,
+    modules::{BarPosition, ModuleMsg, Modules, ViewTrait},
+    utils::{animation::mix, style::container_style},
 };
 
 pub struct LabelView {
@@ -22,43 +22,115 @@ pub struct LabelView {
 impl ViewTrait<Modules> for LabelView {
     fn view<'a>(
         &'a self,
-        _modules: &'a Modules,
+        modules: &'a Modules,
         layout: &'a config::Layout,
     ) -> Element<'a> {
-        let mut content = Container::new(
-            text(self.config.text.clone()).size(self.config.size),
-        );
+        let shaping = self.config.style.text_shaping.into();
+        let watched = self
+            .config
+            .watch_file
+            .as_ref()
+            .and_then(|path| modules.watched_files.get(path)?.as_deref());
+        let mut content = if let Some(contents) = watched {
+            Container::new(
+                text(contents).size(self.config.size).shaping(shaping),
+            )
+        } else if self.config.lines.is_empty() {
+            Container::new(
+                text(self.config.text.clone())
+                    .size(self.config.size)
+                    .shaping(shaping),
+            )
+        } else {
+            Container::new(self.config.lines.iter().fold(
+                Column::new().align_x(Alignment::Center),
+                |col, line| {
+                    let mut line_text =
+                        text(&line.text).size(line.size).shaping(shaping);
+                    if let Some(color) = line.color {
+                        line_text = line_text.color(color);
+                    }
+                    col.push(line_text)
+                },
+            ))
+        };
         content = container_style(content, &self.config.style, layout)
             .id(self.id.clone());
 
+        if let Some(hover_color) = self.config.hover_text_color {
+            let style = self.config.style.inner;
+            let t = modules.hover.progress(&self.id, Instant::now());
+            content = content.style(move |theme: &Theme| {
+                let base = style.text_color.unwrap_or(theme.palette().text);
+                container::Style {
+                    text_color: Some(mix(base, hover_color, t)),
+                    ..style
+                }
+            });
+        }
+
         if layout.anchor.vertical() {
             content = content.center_x(Length::Fill);
         } else {
             content = content.center_y(Length::Fill);
         }
 
-        let tooltip_id = self.config.tooltip.as_ref().map(|_| self.id.clone());
+        let content = mouse_binds(
+            content,
+            &self.config.binds,
+            self.tooltip_ids().into_iter().next(),
+        );
 
-        mouse_binds(content, &self.config.binds, tooltip_id)
+        if self.config.hover_text_color.is_some() {
+            MouseArea::new(content)
+                .on_enter(Message::Module(ModuleMsg::MouseEntered(
+                    MouseEvent::Label(self.id.clone()),
+                )))
+                .on_exit(Message::Module(ModuleMsg::MouseExited(
+                    MouseEvent::Label(self.id.clone()),
+                )))
+                .into()
+        } else {
+            content
+        }
     }
 
     fn position(&self) -> BarPosition {
         self.position
     }
 
+    fn name(&self) -> &'static str {
+        "label"
+    }
+
+    fn margin(&self) -> f32 {
+        self.config.style.margin
+    }
+
+    fn tooltip_ids(&self) -> Vec<widget::Id> {
+        self.config
+            .tooltip
+            .as_ref()
+            .filter(|_| self.config.show_tooltip)
+            .map(|_| self.id.clone())
+            .into_iter()
+            .collect()
+    }
+
     fn tooltip<'a>(
         &'a self,
-        _service: &'a Modules,
+        modules: &'a Modules,
         id: &widget::Id,
     ) -> Option<Element<'a>> {
-        if *id != self.id {
+        if *id != self.id || !self.config.show_tooltip {
             return None;
         }
 
-        self.config
-            .tooltip
-            .as_ref()
-            .map(|tooltip| Text::new(tooltip.clone()).into())
+        self.config.tooltip.as_ref().map(|tooltip| {
+            modules
+                .tooltip_text(tooltip, self.config.style.text_shaping)
+                .into()
+        })
     }
 
     fn as_any(&self) -> &dyn Any {
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, time::Duration};
 
 use ::image as image_rs;
 use base64::Engine;
@@ -7,22 +7,32 @@ use iced::{
     futures::{
         StreamExt,
         channel::mpsc::Sender as IcedSender,
-        stream::{BoxStream, select_all},
+        stream::{BoxStream, Fuse, select_all},
     },
+    time,
     widget::image,
 };
+use parking_lot::Mutex;
 use tokio_stream::StreamMap;
-use tracing::{debug, error};
-use zbus::{Connection, Proxy, zvariant::OwnedValue};
+use tracing::{debug, error, info};
+use zbus::{
+    Connection, Proxy,
+    zvariant::{ObjectPath, OwnedValue, Value},
+};
 
 use super::mpris_player::PlayerProxy;
 use crate::{
     Message,
+    config::{FollowPlayer, MediaControl},
     modules::{self, ModuleAction},
 };
 
 pub struct MprisService {
     pub players: Vec<(String, MprisPlayer)>,
+    pub follow_player: FollowPlayer,
+    /// Steps scrolled by now-playing text that doesn't fit
+    pub scroll: usize,
+    pub scroll_interval: Option<Duration>,
 }
 
 #[profiling::all_functions]
@@ -30,93 +40,103 @@ impl MprisService {
     pub fn new() -> Self {
         Self {
             players: Vec::new(),
+            follow_player: FollowPlayer::default(),
+            scroll: 0,
+            scroll_interval: None,
         }
     }
 
+    /// The player to show when only one can be shown: the first one that is
+    /// playing, or else the first one.
+    pub fn active_player(&self) -> Option<&MprisPlayer> {
+        self.first_playing(|_| true)
+            .or_else(|| self.players.first().map(|(_, player)| player))
+    }
+
+    /// The first player that is playing and matches `filter`
+    fn first_playing(
+        &self,
+        filter: impl Fn(&MprisPlayer) -> bool,
+    ) -> Option<&MprisPlayer> {
+        self.players
+            .iter()
+            .map(|(_, player)| player)
+            .find(|player| player.status == "Playing" && filter(player))
+    }
+
+    pub fn scroll_subscription(&self) -> Option<Subscription<Message>> {
+        self.scroll_interval.map(|interval| {
+            time::every(interval)
+                .map(|_| Message::Module(modules::ModuleMsg::NowPlayingScroll))
+        })
+    }
+
     pub fn subscription() -> iced::Subscription<Message> {
         Subscription::run(|| {
             #[cfg(feature = "tracy")]
             let _ = tracy_client::span!("mpris sub");
-            iced::stream::channel(100, |mut output: IcedSender<MprisEvent>| async move {
-            let connection = match Connection::session().await {
-                Ok(c) => c,
-                Err(e) => {
-                    error!("mpris stream error: {e}");
-                    return;
-                }
-            };
-
-            let dbus_proxy = Proxy::new(
-                &connection,
-            "org.freedesktop.DBus",
-            "/org/freedesktop/DBus",
-            "org.freedesktop.DBus",
-            ).await.unwrap();
-
-            let mut player_streams = StreamMap::new();
-
-            if let Ok(names) = dbus_proxy.call_method("ListNames", &()).await &&
-                let Ok(names) = names.body().deserialize::<Vec<String>>() {
-                for name in names {
-                    let name1 = name.clone();
-                    if name.starts_with(MPRIS_PREFIX) {
-                        if let Err(e) = output.try_send(get_initial_player_state(&connection, &name).await) {
-                            error!("mpris: {e}");
-                        }
-
-                        if let Ok(stream) = create_player_stream(&connection, name).await {
-                            player_streams.insert(name1, stream.fuse());
-                        }
-                    }
-                }
-            }
-
-            let mut name_owner_stream = dbus_proxy.receive_signal("NameOwnerChanged").await.unwrap().fuse();
-
-            loop {
-                tokio::select! {
-                    signal = name_owner_stream.next() => {
-                        if let Some(signal) = signal
-                            && let Ok((player_name, old, new)) = signal.body().deserialize::<(String, String, String)>()
-                            && player_name.starts_with(MPRIS_PREFIX)
+            iced::stream::channel(
+                100,
+                |mut output: IcedSender<MprisEvent>| async move {
+                    let mut player_streams = StreamMap::new();
+                    let mut backoff = MIN_BACKOFF;
+                    loop {
+                        match watch_players(&mut output, &mut player_streams)
+                            .await
                         {
-                            if !new.is_empty() && old.is_empty() {
-                                if let Err(e) = output.try_send(get_initial_player_state(&connection, &player_name).await) {
-                                    error!( "mpris: {e}");
-                                }
-
-                                let name1 = player_name.clone();
-                                if let Ok(stream) = create_player_stream(&connection, player_name).await {
-                                    player_streams.insert(name1, stream.fuse());
-                                }
-                            } else if new.is_empty() && !old.is_empty()
-                                && let Err(e) = output.try_send( MprisEvent::PlayerVanished { player_name }) {
-                                    error!( "mpris: {e}");
-                                }
+                            Ok(()) => {
+                                backoff = MIN_BACKOFF;
+                                info!("mpris: lost the session bus connection");
+                            }
+                            Err(e) => {
+                                error!("mpris stream error: {e}");
+                            }
                         }
-                    },
-
-                    event_result = player_streams.next(), if !player_streams.is_empty() => {
-                        if let Some((pname, Ok(event))) = event_result {
-                            if let MprisEvent::PlayerVanished {ref player_name} = event
-                                &&  *player_name == pname {
-                                    player_streams.remove(&pname);
-                                }
-                            if let Err(e) = output.try_send(event) {
+
+                        // players are enumerated again after reconnecting
+                        for player_name in player_streams.keys().cloned() {
+                            if let Err(e) =
+                                output.try_send(MprisEvent::PlayerVanished {
+                                    player_name,
+                                })
+                            {
                                 error!("mpris: {e}");
                             }
                         }
+                        player_streams.clear();
+
+                        info!("mpris: reconnecting in {}s", backoff.as_secs());
+                        tokio::time::sleep(backoff).await;
+                        backoff = (backoff * 2).min(MAX_BACKOFF);
                     }
-                }
-            }
+                },
+            )
+        })
+        .map(|f| Message::Module(modules::ModuleMsg::Mpris(f)))
+    }
 
+    pub fn update(&mut self, event: MprisEvent) -> ModuleAction {
+        let track = self.active_track();
+        let action = self.apply_event(event);
+        // a new track starts scrolling from its beginning
+        if self.active_track() != track {
+            self.scroll = 0;
         }
+        action
+    }
 
+    /// Name, title and artists of the active player
+    fn active_track(&self) -> Option<(String, String, String)> {
+        self.active_player().map(|player| {
+            (
+                player.name.clone(),
+                player.title().to_string(),
+                player.artists(),
             )
-        }).map(|f| Message::Module(modules::ModuleMsg::Mpris(f)))
+        })
     }
 
-    pub fn update(&mut self, event: MprisEvent) -> ModuleAction {
+    fn apply_event(&mut self, event: MprisEvent) -> ModuleAction {
         match event {
             MprisEvent::PlayerAppeared {
                 player_name,
@@ -127,84 +147,112 @@ impl MprisService {
                 let mut player = MprisPlayer::new(player_name.clone(), status);
                 let action = player.update_metadata(&metadata);
                 self.players.push((player_name, player));
-                return action;
+                self.or_gradient_update(action)
             }
             MprisEvent::PlayerVanished { player_name } => {
                 debug!("mpris player vanished: {player_name}");
                 self.players.retain(|(name, _)| *name != player_name);
-                let players_with_colors = self
-                    .players
-                    .iter()
-                    .filter(|(_, p)| {
-                        p.colors.is_some() && p.status == "Playing"
-                    })
-                    .collect::<Vec<_>>();
-                if let Some((_, player)) = players_with_colors.first() {
-                    let colors = player.colors.clone();
-                    return ModuleAction::Task(iced::Task::perform(
-                        async move { colors },
-                        modules::ModuleMsg::AudioVisualizerGradientUpdate,
-                    ));
-                }
+                self.gradient_update()
             }
             MprisEvent::PlaybackStatusChanged {
                 player_name,
                 status,
             } => {
                 debug!("{player_name} status changed: {status}");
-                if let Some((_, player)) = self
+                let Some((_, player)) = self
                     .players
                     .iter_mut()
                     .find(|(name, _)| *name == player_name)
-                {
-                    if status == "Playing" {
-                        player.status = status;
-                        let colors = player.colors.clone();
-                        return ModuleAction::Task(Task::perform(
-                            async move { colors },
-                            modules::ModuleMsg::AudioVisualizerGradientUpdate,
-                        ));
-                    }
-                    player.status = status;
-                    let players_with_colors = self
-                        .players
-                        .iter()
-                        .filter(|(_, p)| {
-                            p.colors.is_some() && p.status == "Playing"
-                        })
-                        .collect::<Vec<_>>();
-                    if let Some((_, player)) = players_with_colors.first() {
-                        let colors = player.colors.clone();
-                        return ModuleAction::Task(iced::Task::perform(
-                            async move { colors },
-                            modules::ModuleMsg::AudioVisualizerGradientUpdate,
-                        ));
-                    }
-                    return ModuleAction::Task(Task::perform(
-                        async move { None },
-                        modules::ModuleMsg::AudioVisualizerGradientUpdate,
-                    ));
-                }
+                else {
+                    return ModuleAction::None;
+                };
+                player.status = status;
+                self.gradient_update()
             }
             MprisEvent::MetadataChanged {
                 player_name,
                 metadata,
             } => {
-                if let Some((_, player)) = self
+                let Some((_, player)) = self
                     .players
                     .iter_mut()
                     .find(|(name, _)| *name == player_name)
-                {
-                    return player.update_metadata(&metadata);
-                }
+                else {
+                    return ModuleAction::None;
+                };
+                let action = player.update_metadata(&metadata);
+                self.or_gradient_update(action)
             }
         }
+    }
+
+    /// Colors of the player the audio visualizer follows, if it is playing.
+    /// Without a named player, that's the first playing player with colors.
+    fn gradient(&self) -> Option<Vec<Color>> {
+        let player = match &self.follow_player {
+            FollowPlayer::Active => {
+                self.first_playing(|player| player.colors.is_some())
+            }
+            FollowPlayer::Named(name) => {
+                self.first_playing(|player| player.is_named(name))
+            }
+        };
+        player.and_then(|player| player.colors.clone())
+    }
+
+    fn gradient_update(&self) -> ModuleAction {
+        let gradient = self.gradient();
+        ModuleAction::Task(Task::perform(
+            async move { gradient },
+            modules::ModuleMsg::AudioVisualizerGradientUpdate,
+        ))
+    }
+
+    /// `action` if there is one, or else a gradient update. Album art that is
+    /// still loading updates the gradient once it arrives.
+    fn or_gradient_update(&self, action: ModuleAction) -> ModuleAction {
+        match action {
+            ModuleAction::None => self.gradient_update(),
+            action @ ModuleAction::Task(_) => action,
+        }
+    }
+
+    /// Apply album art fetched for `art_url`. The fetch is async, so by the
+    /// time it finishes the player may have vanished or moved on to another
+    /// track, in which case the art is stale and ignored.
+    pub fn set_art(
+        &mut self,
+        player_name: &str,
+        art_url: &str,
+        art: Option<(image::Handle, Option<Vec<Color>>)>,
+    ) -> ModuleAction {
+        let Some((_, player)) = self.players.iter_mut().find(|(name, p)| {
+            name == player_name && p.art_url.as_deref() == Some(art_url)
+        }) else {
+            debug!("mpris: ignoring stale album art for {player_name}");
+            return ModuleAction::None;
+        };
+
+        if let Some((art, gradient)) = art {
+            player.art = Some(art);
+            player.colors = gradient;
+            return self.gradient_update();
+        }
+
         ModuleAction::None
     }
 }
 
 const MPRIS_PREFIX: &str = "org.mpris.MediaPlayer2.";
 
+const MIN_BACKOFF: Duration = Duration::from_secs(1);
+const MAX_BACKOFF: Duration = Duration::from_secs(60);
+
+type PlayerStreams = StreamMap<
+    String,
+    Fuse<BoxStream<'static, Result<MprisEvent, zbus::Error>>>,
+>;
+
 #[derive(Clone, Debug)]
 pub enum MprisEvent {
     PlayerAppeared {
@@ -231,12 +279,36 @@ pub struct MprisPlayer {
     pub status: String,
     pub artists: Option<String>,
     pub title: Option<String>,
+    pub art_url: Option<String>,
     pub art: Option<image::Handle>,
     pub colors: Option<Vec<Color>>,
 }
 
 #[profiling::all_functions]
 impl MprisPlayer {
+    /// Artists joined with commas. Metadata values are stored as their
+    /// variant string representation, e.g. `["a", "b"]`.
+    pub fn artists(&self) -> String {
+        self.artists
+            .as_deref()
+            .unwrap_or_default()
+            .trim_start_matches('[')
+            .trim_end_matches(']')
+            .split(',')
+            .map(|s| s.trim().trim_matches('"'))
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+
+    pub fn title(&self) -> &str {
+        self.title
+            .as_deref()
+            .unwrap_or_default()
+            .trim()
+            .trim_matches('"')
+    }
+
     pub fn update_metadata(
         &mut self,
         metadata: &HashMap<String, OwnedValue>,
@@ -248,8 +320,10 @@ impl MprisPlayer {
             self.artists = Some(val.to_string());
         }
 
+        self.art_url = None;
         if let Some(val) = metadata.get("mpris:artUrl") {
             let art_url = val.to_string().trim_matches('"').to_string();
+            self.art_url = Some(art_url.clone());
             match self.get_art(art_url) {
                 PlayerArt::Async(task) => {
                     return ModuleAction::Task(task);
@@ -257,14 +331,8 @@ impl MprisPlayer {
                 PlayerArt::Sync(art) => {
                     if let Some((handle, colors)) = art {
                         self.art = Some(handle);
-                        self.colors.clone_from(&colors);
-                        if self.status == "Playing" {
-                            let captured_colors = colors;
-                            return ModuleAction::Task(iced::Task::perform(
-                                async move { captured_colors },
-                                modules::ModuleMsg::AudioVisualizerGradientUpdate,
-                            ));
-                        }
+                        self.colors = colors;
+                        return ModuleAction::None;
                     }
                 }
                 PlayerArt::None => {
@@ -277,37 +345,48 @@ impl MprisPlayer {
 
         self.art = None;
         self.colors = None;
-        ModuleAction::Task(Task::perform(
-            async { None },
-            modules::ModuleMsg::AudioVisualizerGradientUpdate,
-        ))
+        ModuleAction::None
+    }
+
+    /// Whether this is the player `name`, with or without the mpris prefix
+    /// and instance suffix, e.g. `spotify` for
+    /// `org.mpris.MediaPlayer2.spotify.instance123`
+    pub fn is_named(&self, name: &str) -> bool {
+        let own = self.name.strip_prefix(MPRIS_PREFIX).unwrap_or(&self.name);
+        self.name == name
+            || own == name
+            || own
+                .strip_prefix(name)
+                .is_some_and(|suffix| suffix.starts_with('.'))
     }
 
     pub fn get_art(&self, art_url: String) -> PlayerArt {
-        if let Some(url) = art_url.strip_prefix("data:image/jpeg;base64,") {
+        if art_url.starts_with("data:image/")
+            && let Some((mime, data)) = art_url.split_once(";base64,")
+        {
             let image_bytes =
-                match base64::engine::general_purpose::STANDARD.decode(url) {
+                match base64::engine::general_purpose::STANDARD.decode(data) {
                     Ok(bytes) => bytes,
                     Err(e) => {
                         error!("base64 decode error: {e}");
                         return PlayerArt::None;
                     }
                 };
-            let gradient = image_rs::load_from_memory(&image_bytes)
-                .ok()
-                .and_then(|img| extract_gradient(&img.to_rgb8(), 12));
-            let handle = image::Handle::from_bytes(image_bytes);
-            PlayerArt::Sync(Some((handle, gradient)))
-        } else if let Some(url) = art_url.strip_prefix("file://") {
-            let handle = image::Handle::from_path(url);
-            let gradient = image_rs::open(url)
-                .ok()
-                .and_then(|img| extract_gradient(&img.to_rgb8(), 12));
-            PlayerArt::Sync(Some((handle, gradient)))
+            // the url is the whole image, too long to log
+            PlayerArt::Sync(decode_art(image_bytes, mime))
+        } else if let Some(path) = art_url.strip_prefix("file://") {
+            match std::fs::read(path) {
+                Ok(bytes) => PlayerArt::Sync(decode_art(bytes, &art_url)),
+                Err(e) => {
+                    debug!("failed to read album art {art_url}: {e}");
+                    PlayerArt::Sync(None)
+                }
+            }
         } else if art_url.starts_with("https://")
             || art_url.starts_with("http://")
         {
             let name = self.name.clone();
+            let url = art_url.clone();
             let task = iced::Task::perform(
                 async move {
                     let response = match reqwest::get(&art_url).await {
@@ -327,13 +406,9 @@ impl MprisPlayer {
                         }
                     };
 
-                    let gradient = image_rs::load_from_memory(&image_bytes)
-                        .ok()
-                        .and_then(|img| extract_gradient(&img.to_rgb8(), 12));
-                    let handle = image::Handle::from_bytes(image_bytes);
-                    Some((handle, gradient))
+                    decode_art(image_bytes.to_vec(), &art_url)
                 },
-                |art| modules::ModuleMsg::PlayerArtUpdate(name, art),
+                |art| modules::ModuleMsg::PlayerArtUpdate(name, url, art),
             );
 
             return PlayerArt::Async(task);
@@ -348,6 +423,7 @@ impl MprisPlayer {
             status,
             artists: None,
             title: None,
+            art_url: None,
             art: None,
             colors: None,
         }
@@ -358,20 +434,207 @@ impl MprisPlayer {
 async fn get_initial_player_state(
     connection: &Connection,
     name: &str,
-) -> MprisEvent {
-    let proxy = PlayerProxy::new(connection, name).await.unwrap();
+) -> zbus::Result<MprisEvent> {
+    let proxy = PlayerProxy::new(connection, name).await?;
     let status = proxy
         .playback_status()
         .await
         .unwrap_or_else(|_| "unknown".to_string());
-    let metadata = proxy.metadata().await.unwrap();
-    MprisEvent::PlayerAppeared {
+    let metadata = proxy.metadata().await?;
+    Ok(MprisEvent::PlayerAppeared {
         player_name: name.to_string(),
         status,
         metadata,
+    })
+}
+
+/// Follow mpris players on a new session bus connection. Returns `Ok` once the
+/// connection is lost after having been established. Players being followed
+/// are left in `player_streams`.
+async fn watch_players(
+    output: &mut IcedSender<MprisEvent>,
+    player_streams: &mut PlayerStreams,
+) -> zbus::Result<()> {
+    let connection = Connection::session().await?;
+
+    let dbus_proxy = Proxy::new(
+        &connection,
+        "org.freedesktop.DBus",
+        "/org/freedesktop/DBus",
+        "org.freedesktop.DBus",
+    )
+    .await?;
+
+    let mut name_owner_stream =
+        dbus_proxy.receive_signal("NameOwnerChanged").await?.fuse();
+
+    let names: Vec<String> = dbus_proxy
+        .call_method("ListNames", &())
+        .await?
+        .body()
+        .deserialize()?;
+    for name in names {
+        if name.starts_with(MPRIS_PREFIX) {
+            add_player(&connection, output, player_streams, name).await;
+        }
+    }
+
+    loop {
+        tokio::select! {
+            signal = name_owner_stream.next() => {
+                // signals only stop when the connection is gone
+                let Some(signal) = signal else {
+                    return Ok(());
+                };
+                if let Ok((player_name, old, new)) =
+                    signal.body().deserialize::<(String, String, String)>()
+                    && player_name.starts_with(MPRIS_PREFIX)
+                {
+                    if !new.is_empty() && old.is_empty() {
+                        add_player(
+                            &connection,
+                            output,
+                            player_streams,
+                            player_name,
+                        )
+                        .await;
+                    } else if new.is_empty() && !old.is_empty() {
+                        player_streams.remove(&player_name);
+                        let event = MprisEvent::PlayerVanished { player_name };
+                        if let Err(e) = output.try_send(event) {
+                            error!("mpris: {e}");
+                        }
+                    }
+                }
+            },
+
+            event_result = player_streams.next(), if !player_streams.is_empty() => {
+                if let Some((_, Ok(event))) = event_result
+                    && let Err(e) = output.try_send(event)
+                {
+                    error!("mpris: {e}");
+                }
+            }
+        }
     }
 }
 
+async fn add_player(
+    connection: &Connection,
+    output: &mut IcedSender<MprisEvent>,
+    player_streams: &mut PlayerStreams,
+    name: String,
+) {
+    match get_initial_player_state(connection, &name).await {
+        Ok(event) => {
+            if let Err(e) = output.try_send(event) {
+                error!("mpris: {e}");
+            }
+        }
+        Err(e) => {
+            error!("mpris: {name}: {e}");
+            return;
+        }
+    }
+
+    match create_player_stream(connection, name.clone()).await {
+        Ok(stream) => {
+            player_streams.insert(name, stream.fuse());
+        }
+        Err(e) => error!("mpris: {name}: {e}"),
+    }
+}
+
+/// Session bus connection shared by media controls, opened on first use
+static SESSION: Mutex<Option<Connection>> = Mutex::new(None);
+
+async fn session() -> zbus::Result<Connection> {
+    if let Some(connection) = SESSION.lock().clone() {
+        return Ok(connection);
+    }
+    let connection = Connection::session().await?;
+    *SESSION.lock() = Some(connection.clone());
+    Ok(connection)
+}
+
+/// Send `control` to `player_name`. If the shared connection turns out to be
+/// dead, e.g. because the session bus restarted, it is replaced and the
+/// control is sent once more.
+pub async fn media_control(
+    control: MediaControl,
+    player_name: &str,
+) -> zbus::Result<()> {
+    match send_media_control(&session().await?, control, player_name).await {
+        Err(zbus::Error::InputOutput(e)) => {
+            info!("mpris: session bus connection lost ({e}), reconnecting");
+            SESSION.lock().take();
+            send_media_control(&session().await?, control, player_name).await
+        }
+        result => result,
+    }
+}
+
+async fn send_media_control(
+    connection: &Connection,
+    control: MediaControl,
+    player_name: &str,
+) -> zbus::Result<()> {
+    let player = PlayerProxy::new(connection, player_name).await?;
+    match control {
+        MediaControl::Play => player.play().await,
+        MediaControl::Pause => player.pause().await,
+        MediaControl::PlayPause => player.play_pause().await,
+        MediaControl::Stop => player.stop().await,
+        MediaControl::Next => player.next().await,
+        MediaControl::Previous => player.previous().await,
+        MediaControl::Seek(amount) => seek(&player, player_name, amount).await,
+        MediaControl::Volume(amount) => {
+            let current = player.volume().await?;
+            player
+                .set_volume((current + f64::from(amount)).max(0.0))
+                .await
+        }
+        MediaControl::SetVolume(amount) => {
+            player.set_volume(f64::from(amount.max(0.0))).await
+        }
+        MediaControl::StepVolume { step, max } => {
+            let current = player.volume().await?;
+            // a volume already above max isn't pulled down by scrolling up
+            let max = f64::from(max).max(current);
+            player
+                .set_volume((current + f64::from(step)).clamp(0.0, max))
+                .await
+        }
+    }
+}
+
+/// Seeks `player` by `offset` microseconds. Players that reject relative
+/// seeking are moved to the absolute target with `SetPosition` instead.
+pub async fn seek(
+    player: &PlayerProxy<'_>,
+    player_name: &str,
+    offset: i64,
+) -> zbus::Result<()> {
+    let Err(seek_err) = player.seek(offset).await else {
+        debug!("{player_name}: seeked by {offset}us");
+        return Ok(());
+    };
+
+    let position = player.position().await?;
+    let metadata = player.metadata().await?;
+    let track_id = match metadata.get("mpris:trackid").map(|id| &**id) {
+        Some(Value::ObjectPath(path)) => path.clone(),
+        Some(Value::Str(path)) => ObjectPath::try_from(path.as_str())?,
+        _ => return Err(seek_err),
+    };
+
+    let target = position.saturating_add(offset).max(0);
+    info!(
+        "{player_name}: relative seek failed ({seek_err}), setting position to {target}us"
+    );
+    player.set_position(&track_id, target).await
+}
+
 #[tracing::instrument]
 async fn create_player_stream(
     connection: &Connection,
@@ -429,6 +692,44 @@ enum PlayerArt {
     None,
 }
 
+/// Largest width or height that album art is kept at. Art is only ever shown
+/// small, and extracting the gradient from a huge image is slow.
+const ART_SIZE: u32 = 200;
+
+/// Only hands out art that actually decodes, so that unsupported or
+/// malformed images show the placeholder instead of a broken image
+fn decode_art(
+    bytes: Vec<u8>,
+    url: &str,
+) -> Option<(image::Handle, Option<Vec<Color>>)> {
+    match image_rs::load_from_memory(&bytes) {
+        Ok(img) => {
+            let img = thumbnail(img);
+            let gradient = extract_gradient(&img.to_rgb8(), 12);
+            let handle = image::Handle::from_rgba(
+                img.width(),
+                img.height(),
+                img.into_rgba8().into_raw(),
+            );
+            Some((handle, gradient))
+        }
+        Err(e) => {
+            debug!("failed to decode album art {url}: {e}");
+            None
+        }
+    }
+}
+
+/// Shrinks art larger than [`ART_SIZE`], keeping its aspect ratio
+#[profiling::function]
+fn thumbnail(img: image_rs::DynamicImage) -> image_rs::DynamicImage {
+    if img.width() > ART_SIZE || img.height() > ART_SIZE {
+        img.thumbnail(ART_SIZE, ART_SIZE)
+    } else {
+        img
+    }
+}
+
 #[profiling::function]
 fn generate_gradient(
     palette: Vec<color_thief::Color>,
@@ -494,3 +795,154 @@ fn extract_gradient(
         Err(_) => None,
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn player(name: &str, art_url: &str) -> (String, MprisPlayer) {
+        let mut player =
+            MprisPlayer::new(name.to_string(), "Paused".to_string());
+        player.art_url = Some(art_url.to_string());
+        (name.to_string(), player)
+    }
+
+    fn art() -> Option<(image::Handle, Option<Vec<Color>>)> {
+        Some((image::Handle::from_bytes(Vec::new()), None))
+    }
+
+    #[test]
+    fn thumbnails_keep_aspect_and_gradient() {
+        // mostly red, with some blue on the right
+        let full = image_rs::RgbImage::from_fn(1500, 1000, |x, _| {
+            if x < 900 {
+                image_rs::Rgb([200, 30, 30])
+            } else {
+                image_rs::Rgb([30, 30, 200])
+            }
+        });
+
+        let small = thumbnail(image_rs::DynamicImage::ImageRgb8(full.clone()));
+        assert_eq!((small.width(), small.height()), (ART_SIZE, 133));
+
+        // the gradient starts at the dominant color, which must survive
+        let full_gradient = extract_gradient(&full, 12).unwrap();
+        let small_gradient = extract_gradient(&small.to_rgb8(), 12).unwrap();
+        let (a, b) = (full_gradient[0], small_gradient[0]);
+        for (x, y) in [(a.r, b.r), (a.g, b.g), (a.b, b.b)] {
+            assert!((x - y).abs() < 0.05, "{a:?} differs from {b:?}");
+        }
+
+        let tiny = image_rs::DynamicImage::new_rgb8(64, 64);
+        assert_eq!(thumbnail(tiny).width(), 64);
+    }
+
+    #[test]
+    fn active_player_prefers_playing() {
+        let mut service = MprisService::new();
+        assert!(service.active_player().is_none());
+
+        service.players.push(player("a", "https://example.com/1"));
+        service.players.push(player("b", "https://example.com/2"));
+        assert_eq!(service.active_player().unwrap().name, "a");
+
+        service.players[1].1.status = "Playing".to_string();
+        assert_eq!(service.active_player().unwrap().name, "b");
+    }
+
+    #[test]
+    fn gradient_follows_named_player() {
+        let mut service = MprisService::new();
+        for name in ["org.mpris.MediaPlayer2.a", "org.mpris.MediaPlayer2.b.1"] {
+            let (name, mut player) = player(name, "https://example.com/1");
+            player.status = "Playing".to_string();
+            player.colors = Some(vec![Color::WHITE]);
+            service.players.push((name, player));
+        }
+        service.players[1].1.colors = Some(vec![Color::BLACK]);
+
+        assert_eq!(service.gradient(), Some(vec![Color::WHITE]));
+
+        // players without colors are skipped while following the active one
+        service.players[0].1.colors = None;
+        assert_eq!(service.gradient(), Some(vec![Color::BLACK]));
+        service.players[0].1.colors = Some(vec![Color::WHITE]);
+
+        service.follow_player = FollowPlayer::Named("b".to_string());
+        assert_eq!(service.gradient(), Some(vec![Color::BLACK]));
+
+        service.players[1].1.status = "Paused".to_string();
+        assert_eq!(service.gradient(), None);
+
+        service.follow_player = FollowPlayer::Named("c".to_string());
+        assert_eq!(service.gradient(), None);
+    }
+
+    #[test]
+    fn scroll_restarts_on_new_track() {
+        let mut service = MprisService::new();
+        service.players.push(player("a", "https://example.com/1"));
+        service.scroll = 7;
+
+        let _ = service.update(MprisEvent::PlaybackStatusChanged {
+            player_name: "a".to_string(),
+            status: "Playing".to_string(),
+        });
+        assert_eq!(service.scroll, 7);
+
+        let metadata = HashMap::from([(
+            "xesam:title".to_string(),
+            OwnedValue::try_from(Value::from("Baz")).unwrap(),
+        )]);
+        let _ = service.update(MprisEvent::MetadataChanged {
+            player_name: "a".to_string(),
+            metadata,
+        });
+        assert_eq!(service.scroll, 0);
+    }
+
+    #[test]
+    fn parses_artists_and_title() {
+        let (_, mut player) = player("a", "https://example.com/1");
+        player.artists = Some(r#"["Foo", "Bar"]"#.to_string());
+        player.title = Some(r#""Baz""#.to_string());
+
+        assert_eq!(player.artists(), "Foo, Bar");
+        assert_eq!(player.title(), "Baz");
+    }
+
+    #[test]
+    fn applies_art_for_current_url() {
+        let mut service = MprisService::new();
+        service.players.push(player("a", "https://example.com/1"));
+
+        let _ = service.set_art("a", "https://example.com/1", art());
+
+        assert!(service.players[0].1.art.is_some());
+    }
+
+    #[test]
+    fn ignores_art_for_previous_track() {
+        let mut service = MprisService::new();
+        service.players.push(player("a", "https://example.com/2"));
+
+        let _ = service.set_art("a", "https://example.com/1", art());
+
+        assert!(service.players[0].1.art.is_none());
+    }
+
+    #[test]
+    fn ignores_art_for_vanished_player() {
+        let mut service = MprisService::new();
+        service.players.push(player("a", "https://example.com/1"));
+        service.players.push(player("b", "https://example.com/1"));
+
+        let _ = service.update(MprisEvent::PlayerVanished {
+            player_name: "a".to_string(),
+        });
+        let _ = service.set_art("a", "https://example.com/1", art());
+
+        assert_eq!(service.players.len(), 1);
+        assert!(service.players[0].1.art.is_none());
+    }
+}
@@ -1,18 +1,19 @@
 use std::any::Any;
 
 use iced::{
-    Length,
-    mouse::{Interaction, ScrollDelta},
+    Font, Length,
+    mouse::Interaction,
     widget::{
-        self, Column, Container, Image, MouseArea, Row, Text, text::Shaping,
+        self, Button, Column, Container, Image, MouseArea, Row, Text, button,
     },
 };
 use rustc_hash::FxHashMap;
 
 use crate::{
-    Element, Message, config,
+    Element, Message,
+    config::{self, MediaControl, MouseBindsForMpris, ScrollBinds},
     modules::{BarPosition, Modules, ViewTrait, mpris::service::MprisPlayer},
-    utils::style::container_style,
+    utils::{gesture, style::container_style},
 };
 
 pub struct MprisView {
@@ -41,6 +42,7 @@ impl ViewTrait<Modules> for MprisView {
                                 player,
                                 &self.config,
                                 layout,
+                                modules.icon_font,
                             ))
                         } else {
                             col
@@ -60,6 +62,7 @@ impl ViewTrait<Modules> for MprisView {
                                 player,
                                 &self.config,
                                 layout,
+                                modules.icon_font,
                             ))
                         } else {
                             row
@@ -74,11 +77,36 @@ impl ViewTrait<Modules> for MprisView {
         self.position
     }
 
+    fn name(&self) -> &'static str {
+        "mpris"
+    }
+
+    fn margin(&self) -> f32 {
+        self.config.placeholder_style.margin
+    }
+
+    fn is_empty(&self, modules: &Modules) -> bool {
+        modules.mpris.as_ref().is_none_or(|m| m.players.is_empty())
+    }
+
+    fn tooltip_ids(&self) -> Vec<widget::Id> {
+        if !self.config.show_tooltip {
+            return Vec::new();
+        }
+        self.player_views
+            .values()
+            .map(|view| view.id.clone())
+            .collect()
+    }
+
     fn tooltip<'a>(
         &'a self,
         modules: &'a Modules,
         id: &widget::Id,
     ) -> Option<Element<'a>> {
+        if !self.config.show_tooltip {
+            return None;
+        }
         let service = modules.mpris.as_ref().expect("mpris should not be None");
         self.player_views.iter().find_map(|(player_name, view)| {
             if view.id == *id {
@@ -86,13 +114,20 @@ impl ViewTrait<Modules> for MprisView {
                     .players
                     .iter()
                     .find(|(name, _)| name == player_name)
-                    .and_then(|(_, player)| view.render_tooltip(player))
+                    .and_then(|(_, player)| {
+                        view.render_tooltip(player, &self.config, modules)
+                    })
             } else {
                 None
             }
         })
     }
 
+    fn interactive_tooltip(&self, id: &widget::Id) -> bool {
+        self.config.controls_in_tooltip
+            && self.player_views.values().any(|view| view.id == *id)
+    }
+
     fn synchronize(&mut self, modules: &Modules) {
         let service = modules.mpris.as_ref().expect("mpris should not be None");
         let player_names: Vec<&String> = service
@@ -145,6 +180,7 @@ impl<'a> MprisPlayerView {
         player: &'a MprisPlayer,
         config: &'a config::Mpris,
         layout: &'a config::Layout,
+        icon_font: Font,
     ) -> Element<'a> {
         let content: Element<'a> = if let Some(art) = &player.art {
             Container::new(Image::new(art)).into()
@@ -152,6 +188,8 @@ impl<'a> MprisPlayerView {
             let container = Container::new(
                 Text::new(config.placeholder.clone())
                     .size(20)
+                    .font(icon_font)
+                    .shaping(config.placeholder_style.text_shaping.into())
                     .width(Length::Fill)
                     .height(Length::Fill)
                     .center(),
@@ -171,106 +209,112 @@ impl<'a> MprisPlayerView {
             }
         };
 
-        let binds = &config.binds;
-
-        let mut mouse_area = MouseArea::new(content)
-            .on_enter(Message::OpenTooltip(self.id.clone()))
-            .on_exit(Message::CloseTooltip(self.id.clone()));
-
-        if let Some(left) = &binds.mouse_left {
+        let mut mouse_area = media_binds(content, &config.binds, &player.name);
+        if config.show_tooltip {
             mouse_area = mouse_area
-                .on_release(Message::MediaControl(*left, player.name.clone()));
-        }
-
-        if let Some(double) = &binds.double_click {
-            mouse_area = mouse_area.on_double_click(Message::MediaControl(
-                *double,
-                player.name.clone(),
-            ));
-        }
-
-        if let Some(right) = &binds.mouse_right {
-            mouse_area = mouse_area.on_right_release(Message::MediaControl(
-                *right,
-                player.name.clone(),
-            ));
-        }
-
-        if let Some(middle) = &binds.mouse_middle {
-            mouse_area = mouse_area.on_middle_release(Message::MediaControl(
-                *middle,
-                player.name.clone(),
-            ));
-        }
-
-        if binds.scroll_up.is_some()
-            || binds.scroll_down.is_some()
-            || binds.scroll_left.is_some()
-            || binds.scroll_right.is_some()
-        {
-            mouse_area = mouse_area.on_scroll(move |delta| {
-                let (x, y) = match delta {
-                    ScrollDelta::Lines { x, y }
-                    | ScrollDelta::Pixels { x, y } => (x, y),
-                };
-
-                if y > 0.0
-                    && let Some(scroll_up) = &binds.scroll_up
-                {
-                    Message::MediaControl(*scroll_up, player.name.clone())
-                } else if y < 0.0
-                    && let Some(scroll_down) = &binds.scroll_down
-                {
-                    Message::MediaControl(*scroll_down, player.name.clone())
-                } else if x < 0.0
-                    && let Some(scroll_right) = &binds.scroll_right
-                {
-                    Message::MediaControl(*scroll_right, player.name.clone())
-                } else if x > 0.0
-                    && let Some(scroll_left) = &binds.scroll_left
-                {
-                    Message::MediaControl(*scroll_left, player.name.clone())
-                } else {
-                    Message::NoOp
-                }
-            });
-        }
-
-        let content = Container::new(
-            mouse_area
-                .interaction(Interaction::Pointer)
                 .on_enter(Message::OpenTooltip(self.id.clone()))
-                .on_exit(Message::CloseTooltip(self.id.clone())),
-        )
-        .id(self.id.clone());
+                .on_exit(Message::CloseTooltip(self.id.clone()));
+        }
 
-        content.into()
+        Container::new(mouse_area).id(self.id.clone()).into()
     }
 
     pub fn render_tooltip(
         &'a self,
         player: &'a MprisPlayer,
+        config: &'a config::Mpris,
+        modules: &Modules,
     ) -> Option<Element<'a>> {
-        let raw_artists =
-            player.artists.clone().unwrap_or_else(|| "[]".to_string());
-        let raw_title =
-            player.title.clone().unwrap_or_else(|| "\"\"".to_string());
-
-        let artists = raw_artists
-            .trim_start_matches('[')
-            .trim_end_matches(']')
-            .split(',')
-            .map(|s| s.trim().trim_matches('"'))
-            .filter(|s| !s.is_empty())
-            .collect::<Vec<_>>()
-            .join(", ");
-
-        let title = raw_title.trim().trim_matches('"');
+        let icon_font = modules.icon_font;
+        let text_shaping = config.placeholder_style.text_shaping;
+        let shaping = text_shaping.into();
+        let artists = player.artists();
+        let title = player.title();
+
+        let text =
+            modules.tooltip_text(format!("{artists} - {title}"), text_shaping);
+
+        if !config.controls_in_tooltip {
+            return Some(text.into());
+        }
+
+        let control = |icon: &'static str, control: MediaControl| {
+            Button::new(
+                Text::new(icon).size(20).font(icon_font).shaping(shaping),
+            )
+            .style(button::text)
+            .on_press(Message::MediaControl(control, player.name.clone()))
+        };
+
+        let play_pause_icon = if player.status == "Playing" {
+            "󰏤"
+        } else {
+            "󰐊"
+        };
 
         Some(
-            Text::new(format!("{artists} - {title}"))
-                .shaping(Shaping::Advanced)
+            Column::new()
+                .spacing(5)
+                .align_x(iced::Alignment::Center)
+                .push(text)
+                .push(
+                    Row::new()
+                        .spacing(10)
+                        .push(control("󰒮", MediaControl::Previous))
+                        .push(control(play_pause_icon, MediaControl::PlayPause))
+                        .push(control("󰒭", MediaControl::Next)),
+                )
                 .into(),
         )
     }
 }
+
+/// Wrap `content` in a mouse area that sends the configured media controls to
+/// `player_name`
+pub fn media_binds<'a>(
+    content: impl Into<Element<'a>>,
+    binds: &MouseBindsForMpris,
+    player_name: &str,
+) -> MouseArea<'a, Message> {
+    let mut mouse_area = MouseArea::new(content);
+
+    let control = |control: MediaControl| {
+        Message::MediaControl(control, player_name.to_string())
+    };
+
+    if let Some(left) = binds.mouse_left {
+        mouse_area = mouse_area.on_release(gesture::click(control(left)));
+    }
+
+    if let Some(double) = binds.double_click {
+        mouse_area =
+            mouse_area.on_double_click(gesture::click(control(double)));
+    }
+
+    if let Some(right) = binds.mouse_right {
+        mouse_area =
+            mouse_area.on_right_release(gesture::click(control(right)));
+    }
+
+    if let Some(middle) = binds.mouse_middle {
+        mouse_area =
+            mouse_area.on_middle_release(gesture::click(control(middle)));
+    }
+
+    if binds.scroll_up.is_some()
+        || binds.scroll_down.is_some()
+        || binds.scroll_left.is_some()
+        || binds.scroll_right.is_some()
+    {
+        let scroll = ScrollBinds {
+            up: binds.scroll_up.map(control),
+            down: binds.scroll_down.map(control),
+            right: binds.scroll_right.map(control),
+            left: binds.scroll_left.map(control),
+        };
+        mouse_area = mouse_area
+            .on_scroll(move |delta| gesture::scroll(delta, scroll.clone()));
+    }
+
+    mouse_area.interaction(Interaction::Pointer)
+}
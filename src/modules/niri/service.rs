@@ -1,22 +1,23 @@
 use std::{cmp::Ordering, io};
 
 use iced::{
-    Subscription,
+    Point, Subscription,
     futures::{
-        SinkExt as _, StreamExt as _, channel::mpsc::Sender as IcedSender,
+        FutureExt as _, SinkExt as _, StreamExt as _,
+        channel::mpsc::Sender as IcedSender,
     },
 };
-use niri_ipc::{Action, Event, Request, WindowLayout};
+use niri_ipc::{Action, Event, Request, WindowLayout, WorkspaceReferenceArg};
 use rustc_hash::FxHashMap;
 use tokio::{
     net::UnixStream,
     sync::mpsc::{self},
 };
 use tokio_util::codec::{Framed, LinesCodec};
-use tracing::{error, info};
+use tracing::{error, info, warn};
 
 use crate::{
-    Message,
+    Message, dbus,
     icon_cache::{Icon, IconCache},
     modules::{self, ModuleAction},
 };
@@ -71,10 +72,26 @@ pub struct Workspace {
     pub output: Option<String>,
     pub idx: u8,
     pub id: u64,
+    pub name: Option<String>,
     pub is_active: bool,
+    pub is_focused: bool,
     pub windows: FxHashMap<u64, Window>,
 }
 
+impl Workspace {
+    /// Number shown for the workspace. niri's indices go up to 255 and the
+    /// offset can be negative, so neither fits the other's type.
+    pub fn display_index(&self, offset: i8) -> i16 {
+        i16::from(self.idx) + i16::from(offset)
+    }
+
+    /// Whether the workspace is on `output`, or on any output if that's
+    /// `None`. Workspaces without an output only match the latter.
+    pub fn on_output(&self, output: Option<&str>) -> bool {
+        output.is_none_or(|output| self.output.as_deref() == Some(output))
+    }
+}
+
 #[profiling::function]
 fn map_window(window: &niri_ipc::Window, icon_cache: IconCache) -> Window {
     Window {
@@ -100,8 +117,31 @@ impl From<WindowLayout> for Layout {
 #[derive(Debug, Clone)]
 pub enum NiriEvent {
     Ready(mpsc::Sender<Request>),
-    Event(Result<Event, String>),
+    /// Events that arrived together, applied in one update
+    Events(Vec<Result<Event, String>>),
     Action(Action),
+    Drag(DragEvent),
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum DragEvent {
+    /// A window icon was pressed
+    Start(u64),
+    Move(Point),
+    End,
+    Cancel,
+}
+
+/// Distance the pointer has to move before a press on a window icon becomes
+/// a drag
+const DRAG_THRESHOLD: f32 = 8.0;
+
+/// A window icon being dragged onto another workspace
+#[derive(Debug, Clone, Copy)]
+pub struct Drag {
+    pub window_id: u64,
+    origin: Option<Point>,
+    pub active: bool,
 }
 
 pub struct NiriService {
@@ -109,8 +149,15 @@ pub struct NiriService {
     pub windows: FxHashMap<u64, niri_ipc::Window>,
     pub hovered_workspace_id: Option<u64>,
     pub focused_window_id: Option<u64>,
+    /// When each window was last focused, counted in focus changes
+    pub focus_history: FxHashMap<u64, u64>,
+    focus_count: u64,
     pub icon_cache: IconCache,
     pub sender: Option<mpsc::Sender<Request>>,
+    pub track_layout: bool,
+    /// Fallback for when `NIRI_SOCKET` isn't set
+    pub socket: Option<String>,
+    pub drag: Option<Drag>,
 }
 
 #[profiling::all_functions]
@@ -121,24 +168,42 @@ impl NiriService {
             windows: FxHashMap::default(),
             hovered_workspace_id: None,
             focused_window_id: None,
+            focus_history: FxHashMap::default(),
+            focus_count: 0,
             icon_cache,
             sender: None,
+            track_layout: true,
+            socket: None,
+            drag: None,
         }
     }
 
-    pub fn subscription() -> Subscription<Message> {
-        Subscription::run(|| {
+    /// Rebuilds the windows of every workspace, resolving their icons again
+    pub fn remap_windows(&mut self) {
+        self.workspaces.values_mut().for_each(|ws| {
+            ws.windows = self
+                .windows
+                .values()
+                .filter(|w| w.workspace_id == Some(ws.id))
+                .map(|w| (w.id, map_window(w, self.icon_cache.clone())))
+                .collect();
+        });
+    }
+
+    pub fn subscription(&self) -> Subscription<Message> {
+        Subscription::run_with(self.socket.clone(), |socket| {
             #[cfg(feature = "tracy")]
             let _ = tracy_client::span!("niri sub");
+            let socket = socket.clone();
             iced::stream::channel(100, |mut output: IcedSender<NiriEvent>| async move {
                 let (request_tx, mut request_rx) = mpsc::channel(32);
 
-                let socket_path = match std::env::var("NIRI_SOCKET") {
-                    Ok(path) => path,
-                    Err(e) => {
-                        error!("NIRI_SOCKET environment variable not set: {e}");
-                        return;
-                    }
+                let Some(socket_path) = socket_path(socket) else {
+                    warn!(
+                        "NIRI_SOCKET is not set and no socket is configured, \
+                        disabling niri"
+                    );
+                    return;
                 };
 
                 let mut ui_socket = match setup_async_socket(&socket_path).await {
@@ -195,13 +260,18 @@ impl NiriService {
                         maybe_line = event_stream_socket.next() => {
                             match maybe_line {
                                 Some(Ok(line)) => {
-                                    let event: Result<Event, _> = serde_json::from_str(&line);
-                                    let send_result = output.try_send(NiriEvent::Event(
-                                        event.map_err(|e| e.to_string()),
-                                    ));
-                                    if let Err(e) = send_result {
+                                    let mut events = vec![parse_event(&line)];
+                                    let open = drain_ready(
+                                        &mut event_stream_socket,
+                                        &mut events,
+                                    );
+                                    let event = NiriEvent::Events(events);
+                                    if let Err(e) = output.try_send(event) {
                                         error!("niri: {e}");
                                     }
+                                    if !open {
+                                        break;
+                                    }
                                 }
                                 Some(Err(e)) => {
                                     error!("niri event socket error: {e}");
@@ -238,7 +308,22 @@ impl NiriService {
                 self.sender = Some(sender);
                 ModuleAction::None
             }
-            NiriEvent::Event(event) => self.handle_ipc_event(event),
+            NiriEvent::Events(events) => {
+                let tasks: Vec<_> = coalesce(events)
+                    .into_iter()
+                    .filter_map(|event| match self.handle_ipc_event(event) {
+                        ModuleAction::Task(task) => Some(task),
+                        ModuleAction::None => None,
+                    })
+                    .collect();
+                self.publish_focused_workspace();
+                if tasks.is_empty() {
+                    ModuleAction::None
+                } else {
+                    ModuleAction::Task(iced::Task::batch(tasks))
+                }
+            }
+            NiriEvent::Drag(event) => self.drag(event),
             NiriEvent::Action(action) => {
                 let Some(sender) = &self.sender else {
                     error!("niri action triggered before sender was ready.");
@@ -260,6 +345,103 @@ impl NiriService {
             }
         }
     }
+
+    fn set_focused_window(&mut self, id: Option<u64>) {
+        self.focused_window_id = id;
+        if let Some(id) = id {
+            self.focus_count += 1;
+            self.focus_history.insert(id, self.focus_count);
+        }
+    }
+
+    /// Column of the focused window and the number of columns in the focused
+    /// workspace, both starting at 1. `None` when the focused window is
+    /// floating or there is none.
+    pub fn focused_column(&self) -> Option<(usize, usize)> {
+        let workspace = self.workspaces.values().find(|ws| ws.is_focused)?;
+        let window = workspace.windows.get(&self.focused_window_id?)?;
+        let Layout::Scrolling(column, _) = window.layout else {
+            return None;
+        };
+        let columns = workspace
+            .windows
+            .values()
+            .filter_map(|w| match w.layout {
+                Layout::Scrolling(column, _) => Some(column),
+                Layout::Floating => None,
+            })
+            .max()
+            .unwrap_or(column);
+        Some((column, columns))
+    }
+
+    fn drag(&mut self, event: DragEvent) -> ModuleAction {
+        match event {
+            DragEvent::Start(window_id) => {
+                self.drag = Some(Drag {
+                    window_id,
+                    origin: None,
+                    active: false,
+                });
+            }
+            DragEvent::Move(position) => {
+                if let Some(drag) = &mut self.drag {
+                    // the first move after the press stands in for the press
+                    // position, which the mouse area doesn't report
+                    match drag.origin {
+                        None => drag.origin = Some(position),
+                        Some(origin) => {
+                            drag.active |=
+                                origin.distance(position) > DRAG_THRESHOLD;
+                        }
+                    }
+                }
+            }
+            DragEvent::Cancel => self.drag = None,
+            DragEvent::End => {
+                let Some(drag) = self.drag.take() else {
+                    return ModuleAction::None;
+                };
+                let workspace_id = self
+                    .windows
+                    .get(&drag.window_id)
+                    .and_then(|w| w.workspace_id);
+
+                let action = if drag.active {
+                    match self.hovered_workspace_id {
+                        Some(target) if Some(target) != workspace_id => {
+                            Action::MoveWindowToWorkspace {
+                                window_id: Some(drag.window_id),
+                                reference: WorkspaceReferenceArg::Id(target),
+                                focus: false,
+                            }
+                        }
+                        _ => return ModuleAction::None,
+                    }
+                } else if let Some(id) = workspace_id {
+                    // without dragging, the press was a click on the
+                    // workspace the window icon is in
+                    Action::FocusWorkspace {
+                        reference: WorkspaceReferenceArg::Id(id),
+                    }
+                } else {
+                    return ModuleAction::None;
+                };
+                return self.update(NiriEvent::Action(action));
+            }
+        }
+        ModuleAction::None
+    }
+
+    fn publish_focused_workspace(&self) {
+        let focused = self
+            .workspaces
+            .values()
+            .find(|ws| ws.is_focused)
+            .map(|ws| (ws.idx, ws.name.clone()));
+        *dbus::FOCUSED_WORKSPACE.write() = focused;
+    }
+
     fn handle_ipc_event(
         &mut self,
         event: Result<Event, String>,
@@ -279,7 +461,9 @@ impl NiriService {
                         output: ws.output,
                         idx: ws.idx,
                         id: ws.id,
+                        name: ws.name,
                         is_active: ws.is_active,
+                        is_focused: ws.is_focused,
                         windows: self
                             .windows
                             .values()
@@ -293,24 +477,20 @@ impl NiriService {
                     .collect();
             }
             Event::WindowsChanged { windows } => {
-                self.focused_window_id =
-                    windows.iter().find_map(|w| w.is_focused.then_some(w.id));
+                self.set_focused_window(
+                    windows.iter().find_map(|w| w.is_focused.then_some(w.id)),
+                );
                 self.windows = windows.into_iter().map(|w| (w.id, w)).collect();
+                self.focus_history
+                    .retain(|id, _| self.windows.contains_key(id));
 
-                self.workspaces.values_mut().for_each(|ws| {
-                    ws.windows = self
-                        .windows
-                        .values()
-                        .filter(|w| w.workspace_id == Some(ws.id))
-                        .map(|w| (w.id, map_window(w, self.icon_cache.clone())))
-                        .collect();
-                });
+                self.remap_windows();
             }
             Event::WindowOpenedOrChanged { window } => {
                 let window_id = window.id;
 
                 if window.is_focused {
-                    self.focused_window_id = Some(window_id);
+                    self.set_focused_window(Some(window_id));
                 }
 
                 let old_workspace_id =
@@ -338,6 +518,7 @@ impl NiriService {
             }
             Event::WindowClosed { id } => {
                 self.windows.remove(&id);
+                self.focus_history.remove(&id);
                 self.workspaces.values_mut().for_each(|ws| {
                     ws.windows.remove(&id);
                 });
@@ -345,37 +526,40 @@ impl NiriService {
                     self.focused_window_id = None;
                 }
             }
-            Event::WorkspaceActivated { id, .. } => {
-                let output = self.workspaces.iter().find_map(|(wid, ws)| {
-                    if wid == &id { ws.output.clone() } else { None }
-                });
+            Event::WorkspaceActivated { id, focused } => {
+                // only workspaces on the same output as the activated one are
+                // affected, so it has to be known before changing anything
+                let Some(output) =
+                    self.workspaces.get(&id).map(|ws| ws.output.clone())
+                else {
+                    error!("niri: activated unknown workspace {id}");
+                    return ModuleAction::None;
+                };
+
                 for ws in self.workspaces.values_mut() {
+                    if focused {
+                        ws.is_focused = ws.id == id;
+                    }
                     if ws.output == output {
-                        ws.is_active = false;
+                        ws.is_active = ws.id == id;
                     }
                 }
-                if let Some(ws) = self.workspaces.get_mut(&id) {
-                    ws.is_active = true;
-                }
             }
             Event::WindowLayoutsChanged { changes } => {
+                if !self.track_layout {
+                    return ModuleAction::None;
+                }
+
                 for (id, layout) in changes {
                     if let Some(window) = self.windows.get_mut(&id) {
                         window.layout = layout;
                     }
                 }
 
-                self.workspaces.values_mut().for_each(|ws| {
-                    ws.windows = self
-                        .windows
-                        .values()
-                        .filter(|w| w.workspace_id == Some(ws.id))
-                        .map(|w| (w.id, map_window(w, self.icon_cache.clone())))
-                        .collect();
-                });
+                self.remap_windows();
             }
             Event::WindowFocusChanged { id } => {
-                self.focused_window_id = id;
+                self.set_focused_window(id);
             }
             _ => (),
         }
@@ -383,9 +567,256 @@ impl NiriService {
     }
 }
 
-async fn setup_async_socket(
-    path: &str,
-) -> io::Result<Framed<UnixStream, LinesCodec>> {
+impl Drop for NiriService {
+    fn drop(&mut self) {
+        *dbus::FOCUSED_WORKSPACE.write() = None;
+    }
+}
+
+/// `NIRI_SOCKET` takes precedence, so that the configured socket only
+/// matters where the environment variable isn't inherited
+fn socket_path(configured: Option<String>) -> Option<String> {
+    std::env::var("NIRI_SOCKET").ok().or(configured)
+}
+
+/// Upper bound on the events applied in one update, so that a steady stream
+/// still gets rendered
+const MAX_BATCH: usize = 64;
+
+type NiriSocket = Framed<UnixStream, LinesCodec>;
+
+fn parse_event(line: &str) -> Result<Event, String> {
+    serde_json::from_str(line).map_err(|e| e.to_string())
+}
+
+/// Takes the lines niri has already sent without waiting for more, so that a
+/// burst of events (e.g. on a niri config reload) is applied in a single
+/// update. Returns `false` once the socket is closed.
+fn drain_ready(
+    socket: &mut NiriSocket,
+    events: &mut Vec<Result<Event, String>>,
+) -> bool {
+    while events.len() < MAX_BATCH {
+        match socket.next().now_or_never() {
+            Some(Some(Ok(line))) => events.push(parse_event(&line)),
+            Some(Some(Err(e))) => {
+                error!("niri event socket error: {e}");
+                return false;
+            }
+            Some(None) => {
+                info!("niri event socket closed");
+                return false;
+            }
+            None => break,
+        }
+    }
+    true
+}
+
+/// Drops full snapshots that a later snapshot of the same kind replaces
+/// anyway. Incremental events are never dropped.
+fn coalesce(events: Vec<Result<Event, String>>) -> Vec<Result<Event, String>> {
+    let last_workspaces = events
+        .iter()
+        .rposition(|e| matches!(e, Ok(Event::WorkspacesChanged { .. })));
+    let last_windows = events
+        .iter()
+        .rposition(|e| matches!(e, Ok(Event::WindowsChanged { .. })));
+
+    events
+        .into_iter()
+        .enumerate()
+        .filter(|(i, event)| match event {
+            Ok(Event::WorkspacesChanged { .. }) => Some(*i) == last_workspaces,
+            Ok(Event::WindowsChanged { .. }) => Some(*i) == last_windows,
+            _ => true,
+        })
+        .map(|(_, event)| event)
+        .collect()
+}
+
+async fn setup_async_socket(path: &str) -> io::Result<NiriSocket> {
     let stream = UnixStream::connect(path).await?;
     Ok(Framed::new(stream, LinesCodec::new()))
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn workspace(id: u64, output: &str, is_active: bool) -> (u64, Workspace) {
+        let workspace = Workspace {
+            output: Some(output.to_string()),
+            idx: 1,
+            id,
+            name: None,
+            is_active,
+            is_focused: false,
+            windows: FxHashMap::default(),
+        };
+        (id, workspace)
+    }
+
+    #[test]
+    fn display_index_at_the_boundaries() {
+        let (_, mut ws) = workspace(1, "DP-1", true);
+
+        ws.idx = 0;
+        assert_eq!(ws.display_index(0), 0);
+        assert_eq!(ws.display_index(-1), -1);
+        assert_eq!(ws.display_index(i8::MIN), -128);
+
+        ws.idx = 255;
+        assert_eq!(ws.display_index(0), 255);
+        assert_eq!(ws.display_index(1), 256);
+        assert_eq!(ws.display_index(i8::MAX), 382);
+        assert_eq!(ws.display_index(i8::MIN), 127);
+    }
+
+    #[test]
+    fn workspaces_on_output() {
+        let (_, ws) = workspace(1, "DP-1", true);
+        assert!(ws.on_output(None));
+        assert!(ws.on_output(Some("DP-1")));
+        assert!(!ws.on_output(Some("HDMI-A-1")));
+
+        let (_, mut orphan) = workspace(2, "DP-1", false);
+        orphan.output = None;
+        assert!(orphan.on_output(None));
+        assert!(!orphan.on_output(Some("DP-1")));
+    }
+
+    fn active(service: &NiriService, output: &str) -> Vec<u64> {
+        let mut ids: Vec<u64> = service
+            .workspaces
+            .values()
+            .filter(|ws| ws.is_active && ws.output.as_deref() == Some(output))
+            .map(|ws| ws.id)
+            .collect();
+        ids.sort_unstable();
+        ids
+    }
+
+    fn summary(events: &[Result<Event, String>]) -> Vec<String> {
+        events
+            .iter()
+            .map(|event| match event {
+                Ok(Event::WorkspacesChanged { .. }) => "workspaces".into(),
+                Ok(Event::WindowsChanged { .. }) => "windows".into(),
+                Ok(Event::WorkspaceActivated { id, .. }) => format!("{id}"),
+                Ok(other) => format!("{other:?}"),
+                Err(e) => e.clone(),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn coalesce_keeps_latest_snapshots() {
+        let activated =
+            |id| Ok(Event::WorkspaceActivated { id, focused: true });
+        let events = vec![
+            Ok(Event::WorkspacesChanged { workspaces: vec![] }),
+            Ok(Event::WindowsChanged { windows: vec![] }),
+            activated(1),
+            Ok(Event::WorkspacesChanged { workspaces: vec![] }),
+            activated(2),
+            Err("bad line".to_string()),
+        ];
+
+        assert_eq!(
+            summary(&coalesce(events)),
+            ["windows", "1", "workspaces", "2", "bad line"]
+        );
+    }
+
+    fn window(id: u64, layout: Layout) -> (u64, Window) {
+        let window = Window {
+            id,
+            icon: None,
+            layout,
+            title: None,
+            app_id: None,
+        };
+        (id, window)
+    }
+
+    #[test]
+    fn focused_column() {
+        let mut service = NiriService::new(IconCache::new());
+        let (id, mut ws) = workspace(1, "DP-1", true);
+        ws.is_focused = true;
+        ws.windows = [
+            window(10, Layout::Scrolling(1, 1)),
+            window(11, Layout::Scrolling(2, 1)),
+            window(12, Layout::Scrolling(2, 2)),
+            window(13, Layout::Scrolling(3, 1)),
+            window(14, Layout::Floating),
+        ]
+        .into_iter()
+        .collect();
+        service.workspaces.insert(id, ws);
+
+        assert_eq!(service.focused_column(), None);
+
+        service.focused_window_id = Some(12);
+        assert_eq!(service.focused_column(), Some((2, 3)));
+
+        service.focused_window_id = Some(14);
+        assert_eq!(service.focused_column(), None);
+    }
+
+    #[test]
+    fn focus_history_follows_focus_changes() {
+        let mut service = NiriService::new(IconCache::new());
+        for id in [Some(1), Some(2), None, Some(1), Some(3)] {
+            let _ =
+                service.handle_ipc_event(Ok(Event::WindowFocusChanged { id }));
+        }
+        let mut recent: Vec<u64> =
+            service.focus_history.keys().copied().collect();
+        recent.sort_unstable_by_key(|id| {
+            std::cmp::Reverse(service.focus_history[id])
+        });
+        assert_eq!(recent, [3, 1, 2]);
+        assert_eq!(service.focused_window_id, Some(3));
+
+        let _ = service.handle_ipc_event(Ok(Event::WindowClosed { id: 1 }));
+        assert!(!service.focus_history.contains_key(&1));
+    }
+
+    #[test]
+    fn activation_is_per_output() {
+        let mut service = NiriService::new(IconCache::new());
+        service.workspaces = [
+            workspace(1, "DP-1", true),
+            workspace(2, "DP-1", false),
+            workspace(3, "HDMI-A-1", true),
+            workspace(4, "HDMI-A-1", false),
+        ]
+        .into_iter()
+        .collect();
+
+        let activate = |service: &mut NiriService, id, focused| {
+            let _ = service.handle_ipc_event(Ok(Event::WorkspaceActivated {
+                id,
+                focused,
+            }));
+        };
+
+        activate(&mut service, 2, true);
+        assert_eq!(active(&service, "DP-1"), vec![2]);
+        assert_eq!(active(&service, "HDMI-A-1"), vec![3]);
+        assert!(service.workspaces[&2].is_focused);
+
+        activate(&mut service, 4, false);
+        assert_eq!(active(&service, "DP-1"), vec![2]);
+        assert_eq!(active(&service, "HDMI-A-1"), vec![4]);
+        assert!(service.workspaces[&2].is_focused);
+
+        // unknown workspaces don't deactivate anything
+        activate(&mut service, 5, true);
+        assert_eq!(active(&service, "DP-1"), vec![2]);
+        assert_eq!(active(&service, "HDMI-A-1"), vec![4]);
+        assert!(service.workspaces[&2].is_focused);
+    }
+}
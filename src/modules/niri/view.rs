@@ -1,25 +1,22 @@
-use std::any::Any;
+use std::{any::Any, cmp::Reverse};
 
 use iced::{
     Alignment, Length,
     mouse::Interaction,
     padding::{left, top},
-    widget::{
-        self, Column, Container, Image, MouseArea, Row, Svg, Text,
-        text::Shaping,
-    },
+    widget::{self, Column, Container, Image, MouseArea, Row, Svg, Text},
 };
 use itertools::Itertools;
 use niri_ipc::{Action, WorkspaceReferenceArg};
 use rustc_hash::FxHashMap;
 
-use super::service::{NiriEvent, Window, Workspace};
+use super::service::{DragEvent, NiriEvent, Window, Workspace};
 use crate::{
     Element, Message, MouseEvent,
-    config::{self, NiriWindowStyle},
+    config::{self, WindowSort},
     icon_cache::Icon,
     modules::{
-        BarPosition, ModuleMsg, Modules, ViewTrait, mouse_binds,
+        BarPosition, ModuleMsg, Modules, Orientations, ViewTrait, mouse_binds,
         niri::service::NiriService,
     },
     utils::style::{window_style, workspace_style},
@@ -55,6 +52,7 @@ impl ViewTrait<Modules> for NiriView {
             service
                 .workspaces
                 .iter()
+                .filter(|(_, ws)| ws.on_output(self.config.output.as_deref()))
                 .sorted_unstable_by_key(|(_, ws)| ws.idx)
                 .fold(Column::new(), |col, (_, ws)| {
                     if let Some(ws_view) = self.workspace_views.get(&ws.id) {
@@ -80,6 +78,7 @@ impl ViewTrait<Modules> for NiriView {
             service
                 .workspaces
                 .iter()
+                .filter(|(_, ws)| ws.on_output(self.config.output.as_deref()))
                 .sorted_unstable_by_key(|(_, ws)| ws.idx)
                 .fold(Row::new(), |row, (_, ws)| {
                     if let Some(ws_view) = self.workspace_views.get(&ws.id) {
@@ -103,6 +102,19 @@ impl ViewTrait<Modules> for NiriView {
                 .into()
         };
 
+        // always wrapped, so that the widget tree doesn't change when a drag
+        // starts
+        let mut content = MouseArea::new(content);
+        if let Some(drag) = service.drag {
+            content = content
+                .on_move(|position| drag_event(DragEvent::Move(position)))
+                .on_release(drag_event(DragEvent::End))
+                .on_exit(drag_event(DragEvent::Cancel));
+            if drag.active {
+                content = content.interaction(Interaction::Grabbing);
+            }
+        }
+
         mouse_binds(content, &self.config.binds, None)
     }
 
@@ -110,11 +122,38 @@ impl ViewTrait<Modules> for NiriView {
         self.position
     }
 
+    fn name(&self) -> &'static str {
+        "niri"
+    }
+
+    /// Workspaces and their windows are laid out in columns on vertical bars
+    fn supported_orientations(&self) -> Orientations {
+        Orientations::Both
+    }
+
+    fn margin(&self) -> f32 {
+        self.config.style.margin
+    }
+
+    fn tooltip_ids(&self) -> Vec<widget::Id> {
+        if !self.config.show_tooltip {
+            return Vec::new();
+        }
+        self.workspace_views
+            .values()
+            .flat_map(|ws| ws.window_views.values())
+            .map(|view| view.id.clone())
+            .collect()
+    }
+
     fn tooltip<'a>(
         &'a self,
         modules: &'a Modules,
         id: &widget::Id,
     ) -> Option<Element<'a>> {
+        if !self.config.show_tooltip {
+            return None;
+        }
         let service = modules.niri.as_ref().expect("niri should not be None");
         for (ws_id, ws_view) in &self.workspace_views {
             for (win_id, win_view) in &ws_view.window_views {
@@ -124,7 +163,11 @@ impl ViewTrait<Modules> for NiriView {
                         .get(ws_id)
                         .and_then(|ws| ws.windows.get(win_id))
                 {
-                    return win_view.render_tooltip(window);
+                    return win_view.render_tooltip(
+                        modules,
+                        window,
+                        self.config.window_style.base.text_shaping,
+                    );
                 }
             }
         }
@@ -177,69 +220,109 @@ impl WorkspaceView {
         config: &'a config::Niri,
         layout: &config::Layout,
     ) -> Element<'a> {
-        let windows = if layout.anchor.vertical() {
+        let vertical = layout.anchor.vertical();
+        let window_spacing = config.window_spacing(vertical);
+        let padding = config.workspace_padding(vertical);
+        // compact bars only show the workspace numbers
+        let compact = layout.compact();
+
+        let windows = if vertical {
             Container::new(
-                workspace.windows.values().sorted_unstable().fold(
-                    Column::new().align_x(Alignment::Center).push(
-                        Text::new(
-                            workspace.idx as i8 + config.workspace_offset,
-                        )
-                        .size(20),
+                sorted_windows(niri, workspace, config)
+                    .filter(|_| !compact)
+                    .fold(
+                        Column::new()
+                            .align_x(Alignment::Center)
+                            .spacing(window_spacing)
+                            .push(
+                                Text::new(
+                                    workspace
+                                        .display_index(config.workspace_offset),
+                                )
+                                .size(20)
+                                .shaping(
+                                    config
+                                        .workspace_style
+                                        .base
+                                        .text_shaping
+                                        .into(),
+                                ),
+                            ),
+                        |col, window| {
+                            if let Some(view) =
+                                self.window_views.get(&window.id)
+                            {
+                                col.push(view.view(
+                                    window,
+                                    niri.focused_window_id == Some(window.id),
+                                    config,
+                                    layout,
+                                ))
+                            } else {
+                                col
+                            }
+                        },
                     ),
-                    |col, window| {
-                        if let Some(view) = self.window_views.get(&window.id) {
-                            col.push(view.view(
-                                window,
-                                niri.focused_window_id == Some(window.id),
-                                &config.window_style,
-                                layout,
-                            ))
-                        } else {
-                            col
-                        }
-                    },
-                ),
             )
-            .padding(top(3).bottom(3))
+            .padding(top(padding).bottom(padding))
             .width(Length::Fill)
             .align_x(Alignment::Center)
         } else {
             Container::new(
-                workspace.windows.values().sorted_unstable().fold(
-                    Row::new()
-                        .align_y(Alignment::Center)
-                        .spacing(5)
-                        .padding(5)
-                        .push(
-                            Text::new(
-                                workspace.idx as i8 + config.workspace_offset,
-                            )
-                            .size(20),
-                        ),
-                    |row, window| {
-                        if let Some(view) = self.window_views.get(&window.id) {
-                            row.push(view.view(
-                                window,
-                                niri.focused_window_id == Some(window.id),
-                                &config.window_style,
-                                layout,
-                            ))
-                        } else {
-                            row
-                        }
-                    },
-                ),
+                sorted_windows(niri, workspace, config)
+                    .filter(|_| !compact)
+                    .fold(
+                        Row::new()
+                            .align_y(Alignment::Center)
+                            .spacing(window_spacing)
+                            .padding(5)
+                            .push(
+                                Text::new(
+                                    workspace
+                                        .display_index(config.workspace_offset),
+                                )
+                                .size(20)
+                                .shaping(
+                                    config
+                                        .workspace_style
+                                        .base
+                                        .text_shaping
+                                        .into(),
+                                ),
+                            ),
+                        |row, window| {
+                            if let Some(view) =
+                                self.window_views.get(&window.id)
+                            {
+                                row.push(view.view(
+                                    window,
+                                    niri.focused_window_id == Some(window.id),
+                                    config,
+                                    layout,
+                                ))
+                            } else {
+                                row
+                            }
+                        },
+                    ),
             )
-            .padding(left(5).right(5))
+            .padding(left(padding).right(padding))
             .height(Length::Fill)
             .align_y(Alignment::Center)
         };
 
-        let windows = windows.style(workspace_style(
-            workspace.is_active,
-            hovered,
-            &config.workspace_style,
-        ));
+        let windows = windows
+            .style(workspace_style(
+                workspace.is_active,
+                hovered,
+                &config.workspace_style,
+            ))
+            .clip(
+                config
+                    .workspace_style
+                    .get(workspace.is_active, hovered)
+                    .has_radius(),
+            );
 
         MouseArea::new(windows)
             .on_press(Message::Module(ModuleMsg::Niri(NiriEvent::Action(
@@ -258,6 +341,32 @@ impl WorkspaceView {
     }
 }
 
+fn drag_event(event: DragEvent) -> Message {
+    Message::Module(ModuleMsg::Niri(NiriEvent::Drag(event)))
+}
+
+/// The windows of `workspace` in the configured order. Ties are broken by
+/// id, so that windows don't swap places between frames.
+fn sorted_windows<'a>(
+    niri: &NiriService,
+    workspace: &'a Workspace,
+    config: &config::Niri,
+) -> impl Iterator<Item = &'a Window> {
+    let windows = workspace.windows.values();
+    match config.window_sort {
+        WindowSort::Layout if config.track_layout => {
+            windows.sorted_unstable_by(|a, b| a.cmp(b).then(a.id.cmp(&b.id)))
+        }
+        WindowSort::Layout | WindowSort::Id => {
+            windows.sorted_unstable_by_key(|w| w.id)
+        }
+        // windows that were never focused go last
+        WindowSort::Focus => windows.sorted_unstable_by_key(|w| {
+            (Reverse(niri.focus_history.get(&w.id).copied()), w.id)
+        }),
+    }
+}
+
 #[derive(Debug, Eq, PartialEq)]
 struct WindowView {
     id: widget::Id,
@@ -271,33 +380,34 @@ impl WindowView {
         }
     }
 
-    fn render_tooltip<'a>(&self, window: &'a Window) -> Option<Element<'a>> {
-        Some(
-            Text::new(
-                if let Some(title) = &window.title
-                    && !title.is_empty()
-                {
-                    title
-                } else if let Some(app_id) = &window.app_id
-                    && !app_id.is_empty()
-                {
-                    app_id
-                } else {
-                    "?"
-                },
-            )
-            .shaping(Shaping::Advanced)
-            .into(),
-        )
+    fn render_tooltip<'a>(
+        &self,
+        modules: &Modules,
+        window: &'a Window,
+        shaping: config::TextShaping,
+    ) -> Option<Element<'a>> {
+        let text = if let Some(title) = &window.title
+            && !title.is_empty()
+        {
+            title
+        } else if let Some(app_id) = &window.app_id
+            && !app_id.is_empty()
+        {
+            app_id
+        } else {
+            "?"
+        };
+        Some(modules.tooltip_text(text, shaping).into())
     }
 
     fn view<'a>(
         &self,
         window: &'a Window,
         focused: bool,
-        style: &'a NiriWindowStyle,
+        config: &'a config::Niri,
         layout: &config::Layout,
     ) -> Element<'a> {
+        let style = &config.window_style;
         let icon_size = layout.width as f32 * 0.7;
         let placehdoler_text_size = icon_size * 0.6;
         let icon: Element<'a> = match &window.icon {
@@ -328,6 +438,7 @@ impl WindowView {
                         .collect::<String>(),
                     )
                     .size(placehdoler_text_size)
+                    .shaping(style.base.text_shaping.into())
                     .width(Length::Fill)
                     .height(Length::Fill)
                     .center(),
@@ -340,14 +451,20 @@ impl WindowView {
             }
         };
 
-        let mut content = Container::new(MouseArea::new(icon).on_right_press(
-            Message::Module(ModuleMsg::Niri(NiriEvent::Action(
-                Action::FocusWindow { id: window.id },
-            ))),
-        ))
-        .padding(3)
-        .style(window_style(focused, style))
-        .id(self.id.clone());
+        let mut icon = MouseArea::new(icon).on_right_press(Message::Module(
+            ModuleMsg::Niri(NiriEvent::Action(Action::FocusWindow {
+                id: window.id,
+            })),
+        ));
+        if config.drag_to_move {
+            icon = icon.on_press(drag_event(DragEvent::Start(window.id)));
+        }
+
+        let mut content = Container::new(icon)
+            .padding(3)
+            .style(window_style(focused, style))
+            .clip(style.get(focused).has_radius())
+            .id(self.id.clone());
 
         if layout.anchor.vertical() {
             content = content.align_x(Alignment::Center);
@@ -355,6 +472,10 @@ impl WindowView {
             content = content.align_y(Alignment::Center);
         }
 
+        if !config.show_tooltip {
+            return content.into();
+        }
+
         MouseArea::new(content)
             .on_enter(Message::OpenTooltip(self.id.clone()))
             .on_exit(Message::CloseTooltip(self.id.clone()))
@@ -0,0 +1,153 @@
+use std::any::Any;
+
+use iced::{
+    Length,
+    widget::{Column, Container, Text},
+};
+
+use crate::{
+    Element, config,
+    modules::{
+        BarPosition, Modules, Orientations, ViewTrait, mpris::view::media_binds,
+    },
+    utils::style::container_style,
+};
+
+/// Separates the end of scrolling text from its start as it loops around
+const SCROLL_GAP: &str = "   ";
+
+pub struct NowPlayingView {
+    config: config::NowPlaying,
+    pub position: BarPosition,
+}
+
+#[profiling::all_functions]
+impl ViewTrait<Modules> for NowPlayingView {
+    fn view<'a>(
+        &'a self,
+        modules: &'a Modules,
+        layout: &'a config::Layout,
+    ) -> Element<'a> {
+        let service = modules.mpris.as_ref().expect("mpris should not be None");
+        let Some(player) = service.active_player() else {
+            return Column::new().into();
+        };
+
+        let text = self
+            .config
+            .format
+            .replace("{artist}", &player.artists())
+            .replace("{title}", player.title());
+        let text = fit(
+            &text,
+            self.config.max_width,
+            self.config.scroll.then_some(service.scroll),
+        );
+
+        let mut content = container_style(
+            Container::new(
+                Text::new(text)
+                    .size(self.config.size)
+                    .shaping(self.config.style.text_shaping.into()),
+            ),
+            &self.config.style,
+            layout,
+        );
+
+        if layout.anchor.vertical() {
+            content = content.center_x(Length::Fill);
+        } else {
+            content = content.center_y(Length::Fill);
+        }
+
+        media_binds(content, &self.config.binds, &player.name).into()
+    }
+
+    fn position(&self) -> BarPosition {
+        self.position
+    }
+
+    fn name(&self) -> &'static str {
+        "now-playing"
+    }
+
+    /// Scrolling text doesn't fit across a vertical bar
+    fn supported_orientations(&self) -> Orientations {
+        Orientations::Horizontal
+    }
+
+    fn margin(&self) -> f32 {
+        self.config.style.margin
+    }
+
+    fn is_empty(&self, modules: &Modules) -> bool {
+        modules
+            .mpris
+            .as_ref()
+            .is_none_or(|m| m.active_player().is_none())
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl NowPlayingView {
+    pub fn new(config: config::NowPlaying, position: BarPosition) -> Self {
+        Self { config, position }
+    }
+}
+
+/// Fit `text` into `max_width` characters. Longer text is either shown as a
+/// window that has scrolled `scroll` characters into it, or truncated with an
+/// ellipsis.
+fn fit(text: &str, max_width: Option<usize>, scroll: Option<usize>) -> String {
+    let Some(max_width) = max_width else {
+        return text.to_string();
+    };
+    if text.chars().count() <= max_width {
+        return text.to_string();
+    }
+
+    match scroll {
+        Some(scroll) => {
+            let looped: Vec<char> =
+                text.chars().chain(SCROLL_GAP.chars()).collect();
+            looped
+                .iter()
+                .cycle()
+                .skip(scroll % looped.len())
+                .take(max_width)
+                .collect()
+        }
+        None => text
+            .chars()
+            .take(max_width.saturating_sub(1))
+            .chain(std::iter::once('…'))
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn short_text_is_unchanged() {
+        assert_eq!(fit("abc", Some(5), Some(2)), "abc");
+        assert_eq!(fit("abcdef", None, None), "abcdef");
+    }
+
+    #[test]
+    fn long_text_is_truncated_without_scroll() {
+        assert_eq!(fit("abcdef", Some(4), None), "abc…");
+    }
+
+    #[test]
+    fn long_text_scrolls_and_loops() {
+        assert_eq!(fit("abcdef", Some(4), Some(0)), "abcd");
+        assert_eq!(fit("abcdef", Some(4), Some(4)), "ef  ");
+        assert_eq!(fit("abcdef", Some(4), Some(8)), " abc");
+        assert_eq!(fit("abcdef", Some(4), Some(9)), "abcd");
+    }
+}
@@ -0,0 +1,56 @@
+use std::any::Any;
+
+use iced::{
+    Length,
+    widget::{Column, Container},
+};
+
+use crate::{
+    Element,
+    config::{self, SpacerSize},
+    modules::{BarPosition, Modules, ViewTrait},
+};
+
+pub struct SpacerView {
+    config: config::Spacer,
+    pub position: BarPosition,
+}
+
+#[profiling::all_functions]
+impl ViewTrait<Modules> for SpacerView {
+    fn view<'a>(
+        &'a self,
+        _modules: &'a Modules,
+        layout: &'a config::Layout,
+    ) -> Element<'a> {
+        let length = match self.config.size {
+            SpacerSize::Fixed(size) => Length::Fixed(size),
+            SpacerSize::Fill => Length::Fill,
+        };
+
+        let space = Container::new(Column::new());
+        if layout.anchor.vertical() {
+            space.width(Length::Fill).height(length).into()
+        } else {
+            space.width(length).height(Length::Fill).into()
+        }
+    }
+
+    fn position(&self) -> BarPosition {
+        self.position
+    }
+
+    fn name(&self) -> &'static str {
+        "spacer"
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl SpacerView {
+    pub fn new(config: config::Spacer, position: BarPosition) -> Self {
+        Self { config, position }
+    }
+}
@@ -2,7 +2,7 @@ use std::any::Any;
 
 use iced::{
     Length,
-    widget::{self, Container, Text, text},
+    widget::{self, Container, text},
 };
 
 use crate::{
@@ -26,8 +26,12 @@ impl ViewTrait<Modules> for TimeView {
         _modules: &'a Modules,
         layout: &'a config::Layout,
     ) -> Element<'a> {
-        let mut content = Container::new(text(&self.current_time).size(16))
-            .id(self.id.clone());
+        let mut content = Container::new(
+            text(&self.current_time)
+                .size(16)
+                .shaping(self.config.style.text_shaping.into()),
+        )
+        .id(self.id.clone());
         content = container_style(content, &self.config.style, layout);
 
         if layout.anchor.vertical() {
@@ -36,22 +40,46 @@ impl ViewTrait<Modules> for TimeView {
             content = content.center_y(Length::Fill);
         }
 
-        mouse_binds(content, &self.config.binds, Some(self.id.clone()))
+        let tooltip_id = self.config.show_tooltip.then(|| self.id.clone());
+        mouse_binds(content, &self.config.binds, tooltip_id)
     }
 
     fn position(&self) -> BarPosition {
         self.position
     }
 
+    fn name(&self) -> &'static str {
+        "time"
+    }
+
+    fn margin(&self) -> f32 {
+        self.config.style.margin
+    }
+
+    fn tooltip_ids(&self) -> Vec<widget::Id> {
+        self.config
+            .show_tooltip
+            .then(|| self.id.clone())
+            .into_iter()
+            .collect()
+    }
+
     fn tooltip<'a>(
         &'a self,
-        _service: &Modules,
+        modules: &Modules,
         id: &widget::Id,
     ) -> Option<Element<'a>> {
-        if *id != self.id {
+        if *id != self.id || !self.config.show_tooltip {
             return None;
         }
-        Some(Text::new(&self.current_tooltip).into())
+        Some(
+            modules
+                .tooltip_text(
+                    &self.current_tooltip,
+                    self.config.style.text_shaping,
+                )
+                .into(),
+        )
     }
 
     fn synchronize(&mut self, modules: &Modules) {
@@ -1,3 +1,7 @@
+pub mod animation;
+pub mod corner;
+pub mod gesture;
 pub mod log;
+pub mod spacing;
 pub mod style;
 pub mod window;
@@ -0,0 +1,84 @@
+use std::{
+    collections::HashMap,
+    time::{Duration, Instant},
+};
+
+use iced::{Animation, Color, widget};
+
+/// A transition between a hidden and a shown state, e.g. to fade something
+/// in and out
+pub struct Fade {
+    duration: Duration,
+    shown: Animation<bool>,
+}
+
+#[profiling::all_functions]
+impl Fade {
+    /// Starts out hidden
+    pub fn new(duration: Duration) -> Self {
+        Self {
+            duration,
+            shown: Animation::new(false).duration(duration),
+        }
+    }
+
+    pub fn set_shown(&mut self, shown: bool) {
+        self.shown.go_mut(shown, Instant::now());
+    }
+
+    /// How far the transition is towards shown, from 0 to 1
+    pub fn progress(&self, now: Instant) -> f32 {
+        // the animation can't interpolate over no time at all
+        if self.duration.is_zero() {
+            return if self.shown.value() { 1.0 } else { 0.0 };
+        }
+        self.shown.interpolate(0.0, 1.0, now)
+    }
+
+    pub fn is_animating(&self, now: Instant) -> bool {
+        !self.duration.is_zero() && self.shown.is_animating(now)
+    }
+}
+
+/// Hover state of widgets, animated so that styles can fade between their
+/// normal and hovered look
+#[derive(Default)]
+pub struct HoverAnimations {
+    duration: Duration,
+    states: HashMap<widget::Id, Fade>,
+}
+
+#[profiling::all_functions]
+impl HoverAnimations {
+    pub fn set_duration(&mut self, duration: Duration) {
+        self.duration = duration;
+        self.states.clear();
+    }
+
+    pub fn set_hovered(&mut self, id: widget::Id, hovered: bool) {
+        let duration = self.duration;
+        self.states
+            .entry(id)
+            .or_insert_with(|| Fade::new(duration))
+            .set_shown(hovered);
+    }
+
+    /// How far the widget is into its hovered look, from 0 to 1
+    pub fn progress(&self, id: &widget::Id, now: Instant) -> f32 {
+        self.states.get(id).map_or(0.0, |fade| fade.progress(now))
+    }
+
+    pub fn is_animating(&self, now: Instant) -> bool {
+        self.states.values().any(|fade| fade.is_animating(now))
+    }
+}
+
+pub fn mix(from: Color, to: Color, t: f32) -> Color {
+    let lerp = |a: f32, b: f32| a + (b - a) * t;
+    Color {
+        r: lerp(from.r, to.r),
+        g: lerp(from.g, to.g),
+        b: lerp(from.b, to.b),
+        a: lerp(from.a, to.a),
+    }
+}
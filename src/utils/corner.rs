@@ -0,0 +1,70 @@
+use iced::{
+    Color, Length, Point, Renderer, Theme,
+    mouse::Cursor,
+    widget::canvas::{self, Canvas, Frame, Geometry, Path},
+};
+use iced_layershell::reexport::Anchor;
+
+use crate::Element;
+
+/// Fills the corner of the screen next to the end of the bar, minus a quarter
+/// circle, so the bar curves into the rounded corner of the screen
+struct ScreenCorner {
+    color: Color,
+    radius: f32,
+    anchor: Anchor,
+}
+
+impl<Message> canvas::Program<Message> for ScreenCorner {
+    type State = ();
+
+    fn draw(
+        &self,
+        _state: &Self::State,
+        renderer: &Renderer,
+        _theme: &Theme,
+        bounds: iced::Rectangle,
+        _cursor: Cursor,
+    ) -> Vec<Geometry<Renderer>> {
+        let mut frame = Frame::new(renderer, bounds.size());
+        let r = self.radius;
+
+        // the corner of the window that touches both the bar and the screen
+        // edge, and the points a radius away from it along either edge
+        let corner = Point::new(
+            if self.anchor.contains(Anchor::Right) {
+                r
+            } else {
+                0.0
+            },
+            if self.anchor.contains(Anchor::Bottom) {
+                r
+            } else {
+                0.0
+            },
+        );
+        let along_x = Point::new(r - corner.x, corner.y);
+        let along_y = Point::new(corner.x, r - corner.y);
+
+        let fillet = Path::new(|b| {
+            b.move_to(corner);
+            b.line_to(along_x);
+            b.arc_to(corner, along_y, r);
+            b.close();
+        });
+        frame.fill(&fillet, self.color);
+
+        vec![frame.into_geometry()]
+    }
+}
+
+pub fn view<'a>(color: Color, radius: f32, anchor: Anchor) -> Element<'a> {
+    Canvas::new(ScreenCorner {
+        color,
+        radius,
+        anchor,
+    })
+    .width(Length::Fixed(radius))
+    .height(Length::Fixed(radius))
+    .into()
+}
@@ -0,0 +1,205 @@
+use std::time::Instant;
+
+use iced::mouse::ScrollDelta;
+
+use crate::{
+    Message,
+    config::{Gestures, ScrollBinds},
+};
+
+/// Clicks and scrolls from mouse binds are routed through the bar as
+/// gestures, so that touchpad scrolling doesn't also trigger click binds
+#[derive(Debug, Clone)]
+pub enum Gesture {
+    Click(Box<Message>),
+    Scroll {
+        x: f32,
+        y: f32,
+        binds: Box<ScrollBinds>,
+    },
+}
+
+pub fn click(message: Message) -> Message {
+    Message::Gesture(Gesture::Click(Box::new(message)))
+}
+
+pub fn scroll(delta: ScrollDelta, binds: ScrollBinds) -> Message {
+    let (x, y) = match delta {
+        ScrollDelta::Lines { x, y } | ScrollDelta::Pixels { x, y } => (x, y),
+    };
+    Message::Gesture(Gesture::Scroll {
+        x,
+        y,
+        binds: Box::new(binds),
+    })
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ScrollDirection {
+    Up,
+    Down,
+    Left,
+    Right,
+}
+
+impl ScrollDirection {
+    pub fn bind(self, binds: ScrollBinds) -> Option<Message> {
+        match self {
+            ScrollDirection::Up => binds.up,
+            ScrollDirection::Down => binds.down,
+            ScrollDirection::Left => binds.left,
+            ScrollDirection::Right => binds.right,
+        }
+    }
+}
+
+/// Idle until a scroll event arrives, then scrolling until no scroll events
+/// have arrived for `scroll_timeout`. While scrolling, deltas accumulate until
+/// they pass `scroll_threshold`, and clicks within `click_suppression` of the
+/// last scroll event are dropped.
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum GestureState {
+    #[default]
+    Idle,
+    Scrolling {
+        last: Instant,
+        x: f32,
+        y: f32,
+    },
+}
+
+impl GestureState {
+    fn expire(&mut self, config: &Gestures, now: Instant) {
+        if let GestureState::Scrolling { last, .. } = *self
+            && now.duration_since(last) > config.scroll_timeout
+        {
+            *self = GestureState::Idle;
+        }
+    }
+
+    pub fn scroll(
+        &mut self,
+        config: &Gestures,
+        now: Instant,
+        dx: f32,
+        dy: f32,
+    ) -> Option<ScrollDirection> {
+        self.expire(config, now);
+
+        let (mut x, mut y) = match *self {
+            GestureState::Idle => (0.0, 0.0),
+            GestureState::Scrolling { x, y, .. } => (x, y),
+        };
+        x += dx;
+        y += dy;
+
+        let direction = if y.abs() >= x.abs() {
+            (y != 0.0 && y.abs() >= config.scroll_threshold).then_some(
+                if y > 0.0 {
+                    ScrollDirection::Up
+                } else {
+                    ScrollDirection::Down
+                },
+            )
+        } else {
+            (x.abs() >= config.scroll_threshold).then_some(if x > 0.0 {
+                ScrollDirection::Left
+            } else {
+                ScrollDirection::Right
+            })
+        };
+
+        if direction.is_some() {
+            (x, y) = (0.0, 0.0);
+        }
+        *self = GestureState::Scrolling { last: now, x, y };
+
+        direction
+    }
+
+    pub fn click(&mut self, config: &Gestures, now: Instant) -> bool {
+        self.expire(config, now);
+
+        match *self {
+            GestureState::Idle => true,
+            GestureState::Scrolling { last, .. } => {
+                now.duration_since(last) >= config.click_suppression
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+
+    fn config(threshold: f32) -> Gestures {
+        Gestures {
+            scroll_threshold: threshold,
+            scroll_timeout: Duration::from_millis(300),
+            click_suppression: Duration::from_millis(150),
+        }
+    }
+
+    #[test]
+    fn scroll_fires_immediately_without_threshold() {
+        let config = config(0.0);
+        let mut state = GestureState::default();
+        let now = Instant::now();
+
+        assert_eq!(
+            state.scroll(&config, now, 0.0, 1.0),
+            Some(ScrollDirection::Up)
+        );
+        assert_eq!(
+            state.scroll(&config, now, 0.0, -1.0),
+            Some(ScrollDirection::Down)
+        );
+        assert_eq!(
+            state.scroll(&config, now, 1.0, 0.0),
+            Some(ScrollDirection::Left)
+        );
+    }
+
+    #[test]
+    fn scroll_accumulates_until_threshold() {
+        let config = config(10.0);
+        let mut state = GestureState::default();
+        let now = Instant::now();
+
+        assert_eq!(state.scroll(&config, now, 0.0, 4.0), None);
+        assert_eq!(state.scroll(&config, now, 0.0, 4.0), None);
+        assert_eq!(
+            state.scroll(&config, now, 0.0, 4.0),
+            Some(ScrollDirection::Up)
+        );
+        assert_eq!(state.scroll(&config, now, 0.0, 4.0), None);
+    }
+
+    #[test]
+    fn scroll_resets_after_timeout() {
+        let config = config(10.0);
+        let mut state = GestureState::default();
+        let now = Instant::now();
+
+        assert_eq!(state.scroll(&config, now, 0.0, 8.0), None);
+        let later = now + Duration::from_millis(500);
+        assert_eq!(state.scroll(&config, later, 0.0, 8.0), None);
+    }
+
+    #[test]
+    fn click_suppressed_right_after_scroll() {
+        let config = config(0.0);
+        let mut state = GestureState::default();
+        let now = Instant::now();
+
+        assert!(state.click(&config, now));
+        state.scroll(&config, now, 0.0, 1.0);
+        assert!(!state.click(&config, now + Duration::from_millis(50)));
+        assert!(state.click(&config, now + Duration::from_millis(200)));
+        assert!(state.click(&config, now + Duration::from_millis(400)));
+        assert_eq!(state, GestureState::Idle);
+    }
+}
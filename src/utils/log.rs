@@ -1,4 +1,8 @@
-use std::{fs, path::PathBuf};
+use std::{
+    fs,
+    path::PathBuf,
+    sync::atomic::{AtomicBool, Ordering},
+};
 
 use chrono::{DateTime, Duration, Utc};
 use notify_rust::Notification;
@@ -16,6 +20,8 @@ type BoxedLayer<S> =
     Box<dyn tracing_subscriber::layer::Layer<S> + Send + Sync + 'static>;
 pub type LogHandle<S> = reload::Handle<Option<BoxedLayer<S>>, S>;
 
+static NOTIFICATIONS_ENABLED: AtomicBool = AtomicBool::new(true);
+
 const MAX_LOG_FILES: usize = 15;
 const MAX_LOG_AGE_DAYS: i64 = 7;
 
@@ -133,7 +139,15 @@ pub fn get_default_filter() -> EnvFilter {
     })
 }
 
+pub fn set_notifications_enabled(enabled: bool) {
+    NOTIFICATIONS_ENABLED.store(enabled, Ordering::Relaxed);
+}
+
 pub fn notification(msg: &str) {
+    if !NOTIFICATIONS_ENABLED.load(Ordering::Relaxed) {
+        return;
+    }
+
     if let Err(e) = Notification::new().summary(BAR_NAMESPACE).body(msg).show()
     {
         warn!("Failed to send notification: {e:?}");
@@ -0,0 +1,47 @@
+/// Gaps for a section of modules with the given margins, starting with the
+/// gap before the first module and ending with the gap after the last one.
+///
+/// Margins collapse: the gap between two neighbouring modules is the largest
+/// of their margins and `spacing`, never the sum. At the ends of a section
+/// the module's margin collapses with `edge` the same way.
+pub fn collapse_margins(margins: &[f32], spacing: f32, edge: f32) -> Vec<f32> {
+    let Some((first, last)) = margins.first().zip(margins.last()) else {
+        return Vec::new();
+    };
+
+    let mut gaps = Vec::with_capacity(margins.len() + 1);
+    gaps.push(first.max(edge));
+    gaps.extend(
+        margins
+            .windows(2)
+            .map(|pair| pair[0].max(pair[1]).max(spacing)),
+    );
+    gaps.push(last.max(edge));
+    gaps
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_section_has_no_gaps() {
+        assert!(collapse_margins(&[], 5.0, 5.0).is_empty());
+    }
+
+    #[test]
+    fn no_margins_falls_back_to_spacing() {
+        assert_eq!(
+            collapse_margins(&[0.0, 0.0, 0.0], 5.0, 2.0),
+            vec![2.0, 5.0, 5.0, 2.0]
+        );
+    }
+
+    #[test]
+    fn mixed_margins_collapse_to_largest() {
+        assert_eq!(
+            collapse_margins(&[10.0, 0.0, 3.0, 8.0], 5.0, 4.0),
+            vec![10.0, 10.0, 5.0, 8.0, 8.0]
+        );
+    }
+}
@@ -14,15 +14,7 @@ pub fn workspace_style(
     hovered: bool,
     style: &NiriWorkspaceStyle,
 ) -> container::StyleFn<'_, Theme> {
-    let style = if active && hovered {
-        &style.active_hovered
-    } else if active {
-        &style.active
-    } else if hovered {
-        &style.hovered
-    } else {
-        &style.base
-    };
+    let style = style.get(active, hovered);
     Box::new(move |_| style.inner)
 }
 
@@ -30,7 +22,7 @@ pub fn window_style(
     focused: bool,
     style: &NiriWindowStyle,
 ) -> container::StyleFn<'_, Theme> {
-    let style = if focused { &style.focused } else { &style.base };
+    let style = style.get(focused);
     Box::new(move |_| style.inner)
 }
 
@@ -39,7 +31,9 @@ pub fn container_style<'a>(
     style: &'a config::ContainerStyle,
     layout: &'a config::Layout,
 ) -> Container<'a, Message> {
-    let retval = container.style(move |_| style.inner);
+    let retval = container
+        .style(move |_| style.inner)
+        .clip(style.has_radius());
     let padding = style.padding.unwrap_or(0f32);
     if layout.anchor.vertical() {
         retval.padding(top(padding).bottom(padding))
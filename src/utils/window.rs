@@ -2,19 +2,17 @@ use iced_layershell::reexport::{
     Anchor, KeyboardInteractivity, NewLayerShellSettings, OutputOption,
 };
 
-use crate::{
-    BAR_NAMESPACE, Message,
-    config::{self, splat_gaps},
-};
+use crate::{BAR_NAMESPACE, Message, config};
 
+/// Open the bar. A bar without modules doesn't reserve any space.
 #[profiling::function]
 pub fn open_window(
     layout: &config::Layout,
+    empty: bool,
 ) -> (iced::window::Id, iced::Task<Message>) {
-    let size = Some(layout.anchor.calc_size(layout.width));
+    let size = Some(layout.surface_size());
 
-    // top, right, bottom, left
-    let margin = Some(splat_gaps(layout.gaps));
+    let margin = Some(layout.margin());
 
     let layer = layout.layer.into();
     let anchor = layout.anchor.into();
@@ -26,9 +24,13 @@ pub fn open_window(
             size,
             layer,
             anchor,
-            exclusive_zone: Some(layout.width as i32 + layout.gaps),
+            exclusive_zone: Some(if empty {
+                0
+            } else {
+                layout.exclusive_zone()
+            }),
             margin,
-            keyboard_interactivity: KeyboardInteractivity::None,
+            keyboard_interactivity: layout.keyboard_interactivity.into(),
             output_option: OutputOption::None,
             events_transparent: false,
             namespace: Some(BAR_NAMESPACE.to_string()),
@@ -41,13 +43,15 @@ pub fn open_window(
     (id, task)
 }
 
-pub fn open_tooltip_window() -> (iced::window::Id, iced::Task<Message>) {
+pub fn open_tooltip_window(
+    interactive: bool,
+) -> (iced::window::Id, iced::Task<Message>) {
     let id = iced::window::Id::unique();
 
     let msg = Message::NewLayerShell {
         settings: NewLayerShellSettings {
             anchor: Anchor::all(),
-            events_transparent: true,
+            events_transparent: !interactive,
             keyboard_interactivity: KeyboardInteractivity::None,
             ..Default::default()
         },
@@ -58,3 +62,68 @@ pub fn open_tooltip_window() -> (iced::window::Id, iced::Task<Message>) {
 
     (id, task)
 }
+
+/// Open a window for each end of the bar where it meets a rounded screen
+/// corner. Returns the ids along with the corner of the screen each window
+/// sits in.
+pub fn open_corner_windows(
+    layout: &config::Layout,
+) -> (Vec<(iced::window::Id, Anchor)>, iced::Task<Message>) {
+    let radius: f32 = layout.screen_corner_radius.into();
+    if radius <= 0.0 {
+        return (Vec::new(), iced::Task::none());
+    }
+
+    let size = (radius * layout.factor()).ceil() as u32;
+    let (top, right, bottom, left) = layout.margin();
+    let width = layout.scaled_width() as i32;
+
+    // the corners sit next to the bar, along the screen edges it touches
+    let (corners, margin) = match layout.anchor {
+        config::Anchor::Top => (
+            [Anchor::Top | Anchor::Left, Anchor::Top | Anchor::Right],
+            (top + width, right, bottom, left),
+        ),
+        config::Anchor::Bottom => (
+            [
+                Anchor::Bottom | Anchor::Left,
+                Anchor::Bottom | Anchor::Right,
+            ],
+            (top, right, bottom + width, left),
+        ),
+        config::Anchor::Left => (
+            [Anchor::Left | Anchor::Top, Anchor::Left | Anchor::Bottom],
+            (top, right, bottom, left + width),
+        ),
+        config::Anchor::Right => (
+            [Anchor::Right | Anchor::Top, Anchor::Right | Anchor::Bottom],
+            (top, right + width, bottom, left),
+        ),
+    };
+
+    let (windows, tasks) = corners
+        .into_iter()
+        .map(|anchor| {
+            let id = iced::window::Id::unique();
+            let msg = Message::NewLayerShell {
+                settings: NewLayerShellSettings {
+                    size: Some((size, size)),
+                    layer: layout.layer.into(),
+                    anchor,
+                    // margins are measured from the screen edges, not from
+                    // the space left over by the bar
+                    exclusive_zone: Some(-1),
+                    margin: Some(margin),
+                    keyboard_interactivity: KeyboardInteractivity::None,
+                    output_option: OutputOption::None,
+                    events_transparent: true,
+                    namespace: Some(BAR_NAMESPACE.to_string()),
+                },
+                id,
+            };
+            ((id, anchor), iced::Task::done(msg))
+        })
+        .unzip::<_, _, Vec<_>, Vec<_>>();
+
+    (windows, iced::Task::batch(tasks))
+}